@@ -0,0 +1,31 @@
+// crates/aquarium/benches/simulation.rs
+//! Cost of one fixed simulation step for crowded tanks. A 60 fps frame leaves about 16 ms, and a frame usually
+//! runs one step, so anything well below that keeps up.
+//!
+//! Run with `cargo bench --bench simulation`.
+use aquarium::Aquarium;
+use aquarium::config::AquariumConfig;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+
+fn crowded_tank(fish_count: usize, food_count: usize) -> Aquarium {
+    let config = AquariumConfig { fish_count, ..AquariumConfig::default() };
+    let mut aquarium = Aquarium::with_config(1920.0, 1080.0, 7, config).unwrap();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    for _ in 0..food_count { aquarium.add_food(rng.gen_range(0.0..1920.0), rng.gen_range(0.0..900.0)); }
+    aquarium
+}
+
+fn update_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+    for (fish, food) in [(100, 100), (1000, 1000), (3000, 3000)] {
+        // Fish eat pellets every step, so each measured step starts from a freshly built tank.
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{fish}_fish_{food}_food")), &(fish, food), |b, &(fish, food)| {
+            b.iter_batched_ref(|| crowded_tank(fish, food), |aquarium| aquarium.update(), BatchSize::LargeInput);
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, update_step);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+// crates/aquarium/src/bin/render_frames.rs
+//! Renders aquarium frames to PNG without a browser, for preview thumbnails and regression images.
+//!
+//! Usage: `render_frames [--width 1280] [--height 720] [--seed 1] [--ticks 300] [--every 60] [--out frames]`
+//!
+//! The simulation runs for `--ticks` updates and a frame is written every `--every` ticks (and after the last one).
+use aquarium::Aquarium;
+use aquarium::headless::PixmapRenderer;
+
+struct Options { width: u32, height: u32, seed: u64, ticks: u32, every: u32, out: String }
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options { width: 1280, height: 720, seed: 1, ticks: 300, every: 60, out: "frames".to_string() };
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("missing value for {}", flag))?;
+        let number = |v: &str| v.parse::<u64>().map_err(|e| format!("invalid value for {}: {}", flag, e));
+        let count = |v: &str| u32::try_from(number(v)?).map_err(|e| format!("invalid value for {}: {}", flag, e));
+        match flag.as_str() {
+            "--width" => options.width = count(&value)?,
+            "--height" => options.height = count(&value)?,
+            "--seed" => options.seed = number(&value)?,
+            "--ticks" => options.ticks = count(&value)?,
+            "--every" => options.every = count(&value)?.max(1),
+            "--out" => options.out = value,
+            _ => return Err(format!("unknown option {}", flag)),
+        }
+    }
+    if options.width < 1 || options.height < 1 { return Err(format!("frame size must be at least 1x1, got {}x{}", options.width, options.height)); }
+    Ok(options)
+}
+
+fn main() {
+    let options = parse_options().unwrap_or_else(|e| { eprintln!("render_frames: {}", e); std::process::exit(2); });
+    std::fs::create_dir_all(&options.out).unwrap_or_else(|e| { eprintln!("render_frames: cannot create {}: {}", options.out, e); std::process::exit(1); });
+    let mut aquarium = Aquarium::with_size(options.width as f64, options.height as f64, options.seed);
+    for tick in 1..=options.ticks {
+        aquarium.update();
+        if tick % options.every != 0 && tick != options.ticks { continue; }
+        let mut renderer = PixmapRenderer::new(options.width, options.height).unwrap_or_else(|| { eprintln!("render_frames: invalid frame size"); std::process::exit(2); });
+        aquarium.draw(&mut renderer);
+        let path = format!("{}/frame_{:05}.png", options.out, tick);
+        if let Err(e) = renderer.save_png(&path) { eprintln!("render_frames: cannot write {}: {}", path, e); std::process::exit(1); }
+        println!("{}", path);
+    }
+}
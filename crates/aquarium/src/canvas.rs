@@ -0,0 +1,76 @@
+// crates/aquarium/src/canvas.rs
+use web_sys::CanvasRenderingContext2d;
+use crate::renderer::Renderer;
+
+/// Default cap on the backing-store pixel ratio; beyond 2x the extra fill cost rarely pays off for a background.
+pub const DEFAULT_MAX_PIXEL_RATIO: f64 = 2.0;
+
+/// `Renderer` backend that forwards to a browser `CanvasRenderingContext2d`.
+///
+/// Callers draw in CSS pixels. The backing store is sized by `devicePixelRatio` (capped at `max_pixel_ratio`) and
+/// the matching scale is installed as the context's base transform, so drawings stay sharp on high-DPI screens.
+pub struct CanvasRenderer { ctx: CanvasRenderingContext2d, pixel_ratio: f64, max_pixel_ratio: f64, css_size: (f64, f64), pin_css_size: bool }
+impl CanvasRenderer {
+    pub fn new(ctx: CanvasRenderingContext2d) -> Self {
+        Self { ctx, pixel_ratio: 0.0, max_pixel_ratio: DEFAULT_MAX_PIXEL_RATIO, css_size: (0.0, 0.0), pin_css_size: false }
+    }
+    /// Also sets the element's CSS size on every `resize`, for canvases sized by the tank rather than a stylesheet.
+    pub fn pin_css_size(&mut self) { self.pin_css_size = true; }
+    /// Sizes the backing store for a `width` x `height` CSS-pixel canvas at the current device pixel ratio. Does
+    /// nothing if neither changed, because resizing a canvas clears it and resets the context state.
+    pub fn resize(&mut self, width: f64, height: f64) {
+        let device_ratio = web_sys::window().map(|w| w.device_pixel_ratio()).filter(|r| *r > 0.0).unwrap_or(1.0);
+        let pixel_ratio = device_ratio.min(self.max_pixel_ratio);
+        if pixel_ratio == self.pixel_ratio && (width, height) == self.css_size { return; }
+        let Some(canvas) = self.ctx.canvas() else { return; };
+        canvas.set_width((width * pixel_ratio).round() as u32); canvas.set_height((height * pixel_ratio).round() as u32);
+        if self.pin_css_size {
+            let style = canvas.style();
+            style.set_property("width", &format!("{}px", width)).unwrap(); style.set_property("height", &format!("{}px", height)).unwrap();
+        }
+        self.ctx.set_transform(pixel_ratio, 0.0, 0.0, pixel_ratio, 0.0, 0.0).unwrap();
+        self.pixel_ratio = pixel_ratio; self.css_size = (width, height);
+    }
+    pub fn set_max_pixel_ratio(&mut self, max_pixel_ratio: f64) {
+        self.max_pixel_ratio = max_pixel_ratio.max(0.5);
+        let (width, height) = self.css_size;
+        self.resize(width, height);
+    }
+}
+impl Renderer for CanvasRenderer {
+    fn save(&mut self) { self.ctx.save(); }
+    fn restore(&mut self) { self.ctx.restore(); }
+    fn translate(&mut self, x: f64, y: f64) { self.ctx.translate(x, y).unwrap(); }
+    fn rotate(&mut self, angle: f64) { self.ctx.rotate(angle).unwrap(); }
+    fn scale(&mut self, x: f64, y: f64) { self.ctx.scale(x, y).unwrap(); }
+
+    fn begin_path(&mut self) { self.ctx.begin_path(); }
+    fn move_to(&mut self, x: f64, y: f64) { self.ctx.move_to(x, y); }
+    fn line_to(&mut self, x: f64, y: f64) { self.ctx.line_to(x, y); }
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) { self.ctx.arc(x, y, radius, start_angle, end_angle).unwrap(); }
+    #[allow(clippy::too_many_arguments)]
+    fn ellipse(&mut self, x: f64, y: f64, radius_x: f64, radius_y: f64, rotation: f64, start_angle: f64, end_angle: f64) {
+        self.ctx.ellipse(x, y, radius_x, radius_y, rotation, start_angle, end_angle).unwrap();
+    }
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64) { self.ctx.rect(x, y, w, h); }
+    fn close_path(&mut self) { self.ctx.close_path(); }
+
+    fn fill(&mut self) { self.ctx.fill(); }
+    fn stroke(&mut self) { self.ctx.stroke(); }
+    fn clip(&mut self) { self.ctx.clip(); }
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64) { self.ctx.fill_rect(x, y, w, h); }
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64) { self.ctx.stroke_rect(x, y, w, h); }
+
+    fn set_fill_color(&mut self, color: &str) { self.ctx.set_fill_style_str(color); }
+    fn set_fill_linear_gradient(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, stops: &[(f64, &str)]) {
+        let gradient = self.ctx.create_linear_gradient(x0, y0, x1, y1);
+        for &(offset, color) in stops { gradient.add_color_stop(offset as f32, color).unwrap(); }
+        self.ctx.set_fill_style_canvas_gradient(&gradient);
+    }
+    fn set_stroke_color(&mut self, color: &str) { self.ctx.set_stroke_style_str(color); }
+    fn set_line_width(&mut self, width: f64) { self.ctx.set_line_width(width); }
+    fn set_global_alpha(&mut self, alpha: f64) { self.ctx.set_global_alpha(alpha); }
+    fn set_blur(&mut self, radius: f64) {
+        if radius > 0.0 { self.ctx.set_filter(&format!("blur({}px)", radius)); } else { self.ctx.set_filter("none"); }
+    }
+}
@@ -0,0 +1,282 @@
+// crates/aquarium/src/config.rs
+use serde::Deserialize;
+use wasm_bindgen::JsValue;
+use crate::renderer::parse_css_color;
+use crate::species::Species;
+
+/// Upper bound on any single population, to keep a typo in the config from freezing the page.
+const MAX_POPULATION: usize = 5000;
+
+/// Everything about a tank that can be chosen when it is created. Deserialized from the object passed to
+/// `window.startAquarium`; every field is optional and falls back to the defaults below.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AquariumConfig {
+    pub fish_count: usize,
+    /// Species new fish are drawn from, equally likely; list one several times to make it more common. Defaults to
+    /// every species but the predatory `barracuda`.
+    pub species: Vec<Species>,
+    pub crab_count: usize,
+    pub bubble_count: usize,
+    /// God rays per 1000 CSS pixels of tank width; `0` disables them.
+    pub god_ray_density: f64,
+    /// Strength of the fishes' urge to school with their neighbours; `0` makes every fish swim on its own.
+    pub schooling: f64,
+    /// Seed for every random choice in the tank. Omit it for a different tank on every load.
+    pub seed: Option<u64>,
+    /// Cap on the device pixel ratio used for the canvas backing store.
+    pub max_pixel_ratio: f64,
+    /// Size the canvas to the window and follow the window's size, setting the canvas's CSS size to match. Off, the
+    /// tank follows the canvas's own CSS size, which the page's stylesheet sets.
+    pub fill_window: bool,
+    /// How much the tank moves; `auto` follows the visitor's `prefers-reduced-motion` setting.
+    pub motion: Motion,
+    pub palette: Palette,
+    pub castle: CastleConfig,
+    pub layers: Layers,
+    pub interaction: InteractionConfig,
+    pub lifecycle: LifecycleConfig,
+    pub predation: PredationConfig,
+    pub breeding: BreedingConfig,
+}
+impl Default for AquariumConfig {
+    fn default() -> Self {
+        Self {
+            fish_count: 15, species: Species::PEACEFUL.to_vec(), crab_count: 3, bubble_count: 30, god_ray_density: 5.0, schooling: 1.0, seed: None,
+            max_pixel_ratio: crate::canvas::DEFAULT_MAX_PIXEL_RATIO, fill_window: false, motion: Motion::Auto,
+            palette: Palette::default(), castle: CastleConfig::default(), layers: Layers::default(), interaction: InteractionConfig::default(),
+            lifecycle: LifecycleConfig::default(), predation: PredationConfig::default(), breeding: BreedingConfig::default(),
+        }
+    }
+}
+
+/// Scene colors, as CSS color strings.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Palette {
+    pub water_top: String,
+    pub water_bottom: String,
+    /// Base color of the god rays; their alpha is animated on top of it.
+    pub god_ray: String,
+    pub sand: String,
+    pub castle_stones: Vec<String>,
+    pub castle_shadow: String,
+    pub crab: String,
+    pub food: String,
+    pub bubble: String,
+    pub bubble_outline: String,
+    /// Fish body colors to pick from, overriding every species' own colors. Empty keeps the species colors.
+    pub fish: Vec<String>,
+}
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            water_top: "#005c97".into(), water_bottom: "#06223b".into(), god_ray: "rgb(210, 230, 255)".into(),
+            sand: "#c2b280".into(), castle_stones: vec!["#6c757d".into(), "#60686f".into(), "#788088".into()],
+            castle_shadow: "#212529".into(), crab: "#d14124".into(), food: "#f0e68c".into(),
+            bubble: "rgba(220, 235, 255, 0.6)".into(), bubble_outline: "rgba(255, 255, 255, 0.8)".into(), fish: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CastleConfig {
+    pub enabled: bool,
+    /// Horizontal position of the castle's center as a fraction of the tank width.
+    pub position: f64,
+}
+impl Default for CastleConfig {
+    fn default() -> Self { Self { enabled: true, position: 0.5 } }
+}
+
+/// Which parts of the scene are drawn. Hidden god rays and bubbles are not simulated either, since nothing else
+/// depends on them; the castle is toggled through `CastleConfig::enabled`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Layers { pub god_rays: bool, pub seafloor: bool, pub bubbles: bool, pub crabs: bool, pub fish: bool, pub food: bool }
+impl Default for Layers {
+    fn default() -> Self { Self { god_rays: true, seafloor: true, bubbles: true, crabs: true, fish: true, food: true } }
+}
+impl Layers {
+    /// Looks up a layer flag by the camelCase name used in the config.
+    pub fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "godRays" => Some(&mut self.god_rays), "seafloor" => Some(&mut self.seafloor), "bubbles" => Some(&mut self.bubbles),
+            "crabs" => Some(&mut self.crabs), "fish" => Some(&mut self.fish), "food" => Some(&mut self.food),
+            _ => None,
+        }
+    }
+}
+
+/// What a pointer gesture does in the tank. `tap` knocks on the glass, startling the fish nearby.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Action { Food, Fish, Tap, None }
+
+/// How the fish take to the pointer. Friendly fish come to look at a slow pointer and only scatter from a hard
+/// flick or a tap on the glass; skittish fish keep away and bolt from any quick movement; indifferent fish only
+/// notice taps.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Temperament { Friendly, Skittish, Indifferent }
+
+/// Calm mode is for visitors who prefer reduced motion: god rays hold still, bubbles drift up slowly, fish swim and turn
+/// gently and the seafloor stops rolling. Still mode shows a single frame that only changes when the tank is
+/// interacted with.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Motion {
+    /// Full motion, or calm when the visitor prefers reduced motion.
+    Auto,
+    Full, Calm, Still,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InteractionConfig {
+    /// Action for a left click, or a tap of a finger or pen. Every finger taps on its own, so several fingers
+    /// feed at several spots.
+    pub click: Action,
+    /// Action for a right click or a pen's barrel button. Anything but `none` suppresses the browser's context menu.
+    pub right_click: Action,
+    /// Action for a finger or pen held still, the touch equivalent of a right click.
+    pub long_press: Action,
+    /// How long a press must be held to count as a long press, in milliseconds.
+    pub long_press_ms: f64,
+    /// Whether pressing on a fish picks it up to drag it around, instead of counting as a click.
+    pub drag_fish: bool,
+    pub temperament: Temperament,
+    /// Distance in pixels within which fish notice the pointer.
+    pub pointer_radius: f64,
+    /// Whether the tank responds to the keyboard while focused: F feeds, N adds a fish, Space pauses, the arrow keys
+    /// move the feeding spot, Enter drops food there and T taps the glass.
+    pub keyboard: bool,
+}
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self {
+            click: Action::Food, right_click: Action::Fish, long_press: Action::Fish, long_press_ms: 500.0, drag_fish: true,
+            temperament: Temperament::Friendly, pointer_radius: 180.0, keyboard: true,
+        }
+    }
+}
+
+/// How fish get hungry, grow and age.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LifecycleConfig {
+    /// Seconds for a fish that has just eaten its fill to become as hungry as it gets.
+    pub hunger_seconds: f64,
+    /// Growth per pellet eaten, as a fraction of its adult size.
+    pub growth_per_meal: f64,
+    /// Largest size a fish can grow to, as a multiple of its adult size.
+    pub max_growth: f64,
+    /// Whether fish die of old age, fading out belly-up. Without it they live forever.
+    pub natural_death: bool,
+    /// Average lifespan in seconds when `natural_death` is on; each fish lives 75% to 125% of it.
+    pub lifespan_seconds: f64,
+}
+impl Default for LifecycleConfig {
+    fn default() -> Self { Self { hunger_seconds: 90.0, growth_per_meal: 0.04, max_growth: 1.5, natural_death: false, lifespan_seconds: 600.0 } }
+}
+
+/// How predators and their prey behave.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PredationConfig {
+    /// Whether a predator that catches its prey eats it. When off, predators only chase, and give up once they
+    /// get close, which keeps a calm background free of casualties.
+    pub kills: bool,
+    /// Distance in pixels at which prey notice a predator and flee.
+    pub flee_radius: f64,
+}
+impl Default for PredationConfig {
+    fn default() -> Self { Self { kills: false, flee_radius: 120.0 } }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BreedingConfig {
+    /// Whether well-fed adults of the same species that meet now and then spawn fry.
+    pub enabled: bool,
+    /// Population the tank settles around: breeding slows as the living fish approach it and stops there. Pair it
+    /// with `lifecycle.naturalDeath` for a tank that renews itself.
+    pub carrying_capacity: usize,
+    /// Seconds a fish rests after breeding before it can breed again.
+    pub cooldown_seconds: f64,
+}
+impl Default for BreedingConfig {
+    fn default() -> Self { Self { enabled: false, carrying_capacity: 30, cooldown_seconds: 60.0 } }
+}
+
+impl AquariumConfig {
+    /// Checks the ranges and colors serde cannot, returning a message naming the offending field.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, count) in [("fishCount", self.fish_count), ("crabCount", self.crab_count), ("bubbleCount", self.bubble_count)] {
+            Self::check_population(name, count)?;
+        }
+        if self.species.is_empty() { return Err("species must list at least one species".into()); }
+        if !(0.0..=50.0).contains(&self.god_ray_density) { return Err(format!("godRayDensity must be between 0 and 50, got {}", self.god_ray_density)); }
+        if !(0.0..=5.0).contains(&self.schooling) { return Err(format!("schooling must be between 0 and 5, got {}", self.schooling)); }
+        let life = &self.lifecycle;
+        if !(life.hunger_seconds > 0.0 && life.hunger_seconds.is_finite()) { return Err(format!("lifecycle.hungerSeconds must be positive, got {}", life.hunger_seconds)); }
+        if !(0.0..=1.0).contains(&life.growth_per_meal) { return Err(format!("lifecycle.growthPerMeal must be between 0 and 1, got {}", life.growth_per_meal)); }
+        if !(1.0..=5.0).contains(&life.max_growth) { return Err(format!("lifecycle.maxGrowth must be between 1 and 5, got {}", life.max_growth)); }
+        if !(life.lifespan_seconds > 0.0 && life.lifespan_seconds.is_finite()) { return Err(format!("lifecycle.lifespanSeconds must be positive, got {}", life.lifespan_seconds)); }
+        if !(100.0..=5000.0).contains(&self.interaction.long_press_ms) { return Err(format!("interaction.longPressMs must be between 100 and 5000, got {}", self.interaction.long_press_ms)); }
+        if !(0.0..=1000.0).contains(&self.interaction.pointer_radius) { return Err(format!("interaction.pointerRadius must be between 0 and 1000, got {}", self.interaction.pointer_radius)); }
+        if !(0.0..=1000.0).contains(&self.predation.flee_radius) { return Err(format!("predation.fleeRadius must be between 0 and 1000, got {}", self.predation.flee_radius)); }
+        Self::check_population("breeding.carryingCapacity", self.breeding.carrying_capacity)?;
+        if !(self.breeding.cooldown_seconds >= 0.0 && self.breeding.cooldown_seconds.is_finite()) { return Err(format!("breeding.cooldownSeconds must not be negative, got {}", self.breeding.cooldown_seconds)); }
+        if !(self.max_pixel_ratio > 0.0 && self.max_pixel_ratio.is_finite()) { return Err(format!("maxPixelRatio must be positive, got {}", self.max_pixel_ratio)); }
+        if !(0.0..=1.0).contains(&self.castle.position) { return Err(format!("castle.position must be between 0 and 1, got {}", self.castle.position)); }
+        self.palette.validate()
+    }
+
+    /// Rejects population targets above the limit that `validate` enforces at construction time.
+    pub fn check_population(name: &str, count: usize) -> Result<(), String> {
+        if count > MAX_POPULATION { return Err(format!("{} must be at most {}, got {}", name, MAX_POPULATION, count)); }
+        Ok(())
+    }
+
+    /// Reads a config object handed over from JavaScript. `undefined` and `null` give the defaults; malformed or
+    /// out-of-range values are reported as a string error instead of panicking.
+    pub fn from_js(value: JsValue) -> Result<Self, JsValue> {
+        if value.is_undefined() || value.is_null() { return Ok(Self::default()); }
+        let invalid = |e: String| JsValue::from_str(&format!("Invalid aquarium config: {}", e));
+        let config: Self = serde_wasm_bindgen::from_value(value).map_err(|e| invalid(e.to_string()))?;
+        config.validate().map_err(invalid)?;
+        Ok(config)
+    }
+}
+impl Palette {
+    pub fn validate(&self) -> Result<(), String> {
+        let p = self;
+        let singles = [
+            ("waterTop", &p.water_top), ("waterBottom", &p.water_bottom), ("godRay", &p.god_ray), ("sand", &p.sand),
+            ("castleShadow", &p.castle_shadow), ("crab", &p.crab), ("food", &p.food), ("bubble", &p.bubble), ("bubbleOutline", &p.bubble_outline),
+        ];
+        let lists = p.castle_stones.iter().map(|c| ("castleStones", c)).chain(p.fish.iter().map(|c| ("fish", c)));
+        for (name, color) in singles.into_iter().chain(lists) {
+            if parse_css_color(color).is_none() { return Err(format!("palette.{} is not a supported color: {:?}", name, color)); }
+        }
+        if p.castle_stones.is_empty() { return Err("palette.castleStones must list at least one color".into()); }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation_names_the_offending_field() {
+        assert_eq!(AquariumConfig::default().validate(), Ok(()));
+        let off_center = AquariumConfig { castle: CastleConfig { enabled: true, position: 1.5 }, ..AquariumConfig::default() };
+        assert!(off_center.validate().unwrap_err().contains("castle.position"));
+        let mut bad_color = AquariumConfig::default();
+        bad_color.palette.fish = vec!["#ff8800".into(), "teal-ish".into()];
+        assert!(bad_color.validate().unwrap_err().contains("palette.fish"));
+    }
+}
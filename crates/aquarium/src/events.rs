@@ -0,0 +1,47 @@
+// crates/aquarium/src/events.rs
+use serde::Serialize;
+use crate::species::Species;
+
+/// Something that happened in the tank, as handed to JavaScript listeners: an object whose `type` is the event's name
+/// (`foodDropped`, `foodEaten`, `fishSpawned`, `fishDied`, `crabStateChanged` or `bubblePopped`) alongside its fields.
+/// `fish` and `crab` are ids that stay with the creature for its whole life.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TankEvent {
+    FoodDropped { x: f64, y: f64 },
+    /// `x` and `y` are where the pellet was eaten.
+    FoodEaten { fish: u32, species: Species, x: f64, y: f64 },
+    FishSpawned { fish: u32, species: Species, x: f64, y: f64, cause: SpawnCause },
+    FishDied { fish: u32, species: Species, x: f64, y: f64, cause: DeathCause },
+    CrabStateChanged { crab: u32, state: CrabState, x: f64 },
+    /// A bubble reached the surface.
+    BubblePopped { x: f64 },
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SpawnCause {
+    /// Added by a click, a key or `add_fish`.
+    Added,
+    /// Swam in from the side after the population target was raised.
+    SwamIn,
+    /// Hatched from two parents.
+    Hatched,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DeathCause { OldAge, Eaten }
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CrabState { Walking, Waiting }
+
+/// The events since they were last taken, collected only while someone is listening.
+#[derive(Default)]
+pub(crate) struct EventLog { listening: bool, events: Vec<TankEvent> }
+impl EventLog {
+    pub(crate) fn push(&mut self, event: TankEvent) { if self.listening { self.events.push(event); } }
+    pub(crate) fn listen(&mut self, listening: bool) { self.listening = listening; if !listening { self.events.clear(); } }
+    pub(crate) fn take(&mut self) -> Vec<TankEvent> { std::mem::take(&mut self.events) }
+}
@@ -0,0 +1,186 @@
+// crates/aquarium/src/headless.rs
+use tiny_skia::{
+    Color, FillRule, GradientStop, LinearGradient, Mask, Paint, PathBuilder, Pixmap, PixmapPaint, Point, SpreadMode,
+    Stroke, Transform,
+};
+use crate::renderer::{parse_css_color, Renderer};
+
+#[derive(Clone)]
+struct State { transform: Transform, fill: Paint<'static>, stroke: Paint<'static>, line_width: f64, blur: f64, alpha: f32, clip: Option<Mask> }
+
+/// Software `Renderer` backend that rasterizes into an in-memory `tiny_skia::Pixmap`.
+///
+/// Path points are transformed into device space as they are added, exactly like Canvas 2D, so a transform change
+/// between `begin_path` and `fill` does not move geometry that was already built.
+pub struct PixmapRenderer { pixmap: Pixmap, scratch: Option<Pixmap>, path: PathBuilder, state: State, stack: Vec<State> }
+impl PixmapRenderer {
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        let paint = |color| { let mut paint = Paint::default(); paint.set_color(color); paint.anti_alias = true; paint };
+        let state = State {
+            transform: Transform::identity(), fill: paint(Color::BLACK), stroke: paint(Color::BLACK),
+            line_width: 1.0, blur: 0.0, alpha: 1.0, clip: None,
+        };
+        Some(Self { pixmap: Pixmap::new(width, height)?, scratch: None, path: PathBuilder::new(), state, stack: Vec::new() })
+    }
+    pub fn pixmap(&self) -> &Pixmap { &self.pixmap }
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> Result<(), String> { self.pixmap.save_png(path).map_err(|e| e.to_string()) }
+
+    fn point(&self, x: f64, y: f64) -> Point {
+        let mut p = Point::from_xy(x as f32, y as f32); self.state.transform.map_point(&mut p); p
+    }
+    fn push_point(&mut self, x: f64, y: f64, connect: bool) {
+        let p = self.point(x, y);
+        if connect && !self.path.is_empty() { self.path.line_to(p.x, p.y); } else { self.path.move_to(p.x, p.y); }
+    }
+    fn device_scale(&self) -> f64 { let t = self.state.transform; ((t.sx * t.sy - t.kx * t.ky).abs() as f64).sqrt() }
+    /// Appends an elliptical arc as a polyline; winding follows the Canvas 2D clockwise convention.
+    #[allow(clippy::too_many_arguments)]
+    fn push_arc(&mut self, x: f64, y: f64, rx: f64, ry: f64, rotation: f64, start: f64, end: f64) {
+        let tau = std::f64::consts::PI * 2.0;
+        let sweep = if end - start >= tau { tau } else { (end - start).rem_euclid(tau) };
+        let segments = ((sweep * rx.max(ry) * self.device_scale() / 2.0).ceil() as usize).clamp(8, 256);
+        let (sin_r, cos_r) = rotation.sin_cos();
+        for i in 0..=segments {
+            let angle = start + sweep * i as f64 / segments as f64;
+            let (ex, ey) = (angle.cos() * rx, angle.sin() * ry);
+            self.push_point(x + ex * cos_r - ey * sin_r, y + ex * sin_r + ey * cos_r, true);
+        }
+    }
+    fn paint_path(&mut self, path: &PathBuilder, stroke: bool) {
+        let Some(path) = path.clone().finish() else { return; };
+        let mut paint = if stroke { self.state.stroke.clone() } else { self.state.fill.clone() };
+        if self.state.alpha < 1.0 { paint.shader.apply_opacity(self.state.alpha); }
+        let paint = &paint;
+        let line = Stroke { width: (self.state.line_width * self.device_scale()) as f32, ..Stroke::default() };
+        if self.state.blur <= 0.0 {
+            if stroke { self.pixmap.stroke_path(&path, paint, &line, Transform::identity(), self.state.clip.as_ref()); }
+            else { self.pixmap.fill_path(&path, paint, FillRule::Winding, Transform::identity(), self.state.clip.as_ref()); }
+            return;
+        }
+        // Blurred shapes are painted onto a scratch layer, blurred there, then composited through the clip.
+        let radius = (self.state.blur * self.device_scale()).round() as i64;
+        let (width, height) = (self.pixmap.width(), self.pixmap.height());
+        let scratch = self.scratch.get_or_insert_with(|| Pixmap::new(width, height).unwrap());
+        scratch.fill(Color::TRANSPARENT);
+        if stroke { scratch.stroke_path(&path, paint, &line, Transform::identity(), None); }
+        else { scratch.fill_path(&path, paint, FillRule::Winding, Transform::identity(), None); }
+        let bounds = path.bounds();
+        let margin = radius as f32 * 3.0;
+        let x0 = ((bounds.left() - margin).floor().max(0.0) as usize).min(width as usize);
+        let y0 = ((bounds.top() - margin).floor().max(0.0) as usize).min(height as usize);
+        let x1 = ((bounds.right() + margin).ceil().max(0.0) as usize).min(width as usize);
+        let y1 = ((bounds.bottom() + margin).ceil().max(0.0) as usize).min(height as usize);
+        for _ in 0..3 { box_blur(scratch.data_mut(), width as usize, (x0, y0, x1, y1), radius); }
+        self.pixmap.draw_pixmap(0, 0, scratch.as_ref(), &PixmapPaint::default(), Transform::identity(), self.state.clip.as_ref());
+    }
+}
+
+/// One horizontal and one vertical box-blur pass over the premultiplied RGBA `data` inside `(x0, y0, x1, y1)`. Three passes
+/// approximate a Gaussian whose standard deviation is roughly `radius`, matching CSS `blur(radius px)`.
+fn box_blur(data: &mut [u8], stride: usize, (x0, y0, x1, y1): (usize, usize, usize, usize), radius: i64) {
+    if radius <= 0 || x1 <= x0 || y1 <= y0 { return; }
+    let window = (2 * radius + 1) as u32;
+    let mut line = Vec::new();
+    let mut blur_line = |data: &mut [u8], start: usize, step: usize, len: usize| {
+        line.clear();
+        line.extend((0..len).map(|i| { let o = (start + i * step) * 4; [data[o], data[o + 1], data[o + 2], data[o + 3]] }));
+        let texel = |i: i64| line[i.clamp(0, len as i64 - 1) as usize];
+        let mut sums = [0u32; 4];
+        for i in -radius..=radius { let t = texel(i); for c in 0..4 { sums[c] += t[c] as u32; } }
+        for i in 0..len as i64 {
+            let o = (start + i as usize * step) * 4;
+            for c in 0..4 { data[o + c] = (sums[c] / window) as u8; }
+            let (add, sub) = (texel(i + radius + 1), texel(i - radius));
+            for c in 0..4 { sums[c] = sums[c] + add[c] as u32 - sub[c] as u32; }
+        }
+    };
+    for y in y0..y1 { blur_line(data, y * stride + x0, 1, x1 - x0); }
+    for x in x0..x1 { blur_line(data, y0 * stride + x, stride, y1 - y0); }
+}
+
+impl Renderer for PixmapRenderer {
+    fn save(&mut self) { self.stack.push(self.state.clone()); }
+    fn restore(&mut self) { if let Some(state) = self.stack.pop() { self.state = state; } }
+    fn translate(&mut self, x: f64, y: f64) { self.state.transform = self.state.transform.pre_translate(x as f32, y as f32); }
+    fn rotate(&mut self, angle: f64) { self.state.transform = self.state.transform.pre_concat(Transform::from_rotate(angle.to_degrees() as f32)); }
+    fn scale(&mut self, x: f64, y: f64) { self.state.transform = self.state.transform.pre_scale(x as f32, y as f32); }
+
+    fn begin_path(&mut self) { self.path.clear(); }
+    fn move_to(&mut self, x: f64, y: f64) { self.push_point(x, y, false); }
+    fn line_to(&mut self, x: f64, y: f64) { self.push_point(x, y, true); }
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) { self.push_arc(x, y, radius, radius, 0.0, start_angle, end_angle); }
+    #[allow(clippy::too_many_arguments)]
+    fn ellipse(&mut self, x: f64, y: f64, radius_x: f64, radius_y: f64, rotation: f64, start_angle: f64, end_angle: f64) {
+        self.push_arc(x, y, radius_x, radius_y, rotation, start_angle, end_angle);
+    }
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.push_point(x, y, false); self.push_point(x + w, y, true); self.push_point(x + w, y + h, true); self.push_point(x, y + h, true); self.path.close();
+    }
+    fn close_path(&mut self) { if !self.path.is_empty() { self.path.close(); } }
+
+    fn fill(&mut self) { let path = self.path.clone(); self.paint_path(&path, false); }
+    fn stroke(&mut self) { let path = self.path.clone(); self.paint_path(&path, true); }
+    fn clip(&mut self) {
+        let Some(path) = self.path.clone().finish() else { return; };
+        match &mut self.state.clip {
+            Some(mask) => mask.intersect_path(&path, FillRule::Winding, true, Transform::identity()),
+            None => {
+                let Some(mut mask) = Mask::new(self.pixmap.width(), self.pixmap.height()) else { return; };
+                mask.fill_path(&path, FillRule::Winding, true, Transform::identity());
+                self.state.clip = Some(mask);
+            }
+        }
+    }
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        let saved = std::mem::take(&mut self.path);
+        self.rect(x, y, w, h); self.fill();
+        self.path = saved;
+    }
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        let saved = std::mem::take(&mut self.path);
+        self.rect(x, y, w, h); self.stroke();
+        self.path = saved;
+    }
+
+    fn set_fill_color(&mut self, color: &str) {
+        if let Some(color) = parse_css_color(color).and_then(|[r, g, b, a]| Color::from_rgba(r, g, b, a)) { self.state.fill.set_color(color); }
+    }
+    fn set_fill_linear_gradient(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, stops: &[(f64, &str)]) {
+        let stops = stops.iter().filter_map(|&(offset, color)| {
+            let [r, g, b, a] = parse_css_color(color)?;
+            Some(GradientStop::new(offset as f32, Color::from_rgba(r, g, b, a)?))
+        }).collect();
+        let start = Point::from_xy(x0 as f32, y0 as f32); let end = Point::from_xy(x1 as f32, y1 as f32);
+        if let Some(shader) = LinearGradient::new(start, end, stops, SpreadMode::Pad, self.state.transform) { self.state.fill.shader = shader; }
+    }
+    fn set_stroke_color(&mut self, color: &str) {
+        if let Some(color) = parse_css_color(color).and_then(|[r, g, b, a]| Color::from_rgba(r, g, b, a)) { self.state.stroke.set_color(color); }
+    }
+    fn set_line_width(&mut self, width: f64) { if width > 0.0 { self.state.line_width = width; } }
+    fn set_blur(&mut self, radius: f64) { self.state.blur = radius.max(0.0); }
+    fn set_global_alpha(&mut self, alpha: f64) { self.state.alpha = alpha.clamp(0.0, 1.0) as f32; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AquariumConfig;
+    use crate::Aquarium;
+
+    #[test]
+    fn renders_a_frame_and_writes_it_as_png() {
+        let config = AquariumConfig { fish_count: 0, crab_count: 0, god_ray_density: 0.0, ..AquariumConfig::default() };
+        let aquarium = Aquarium::with_config(160.0, 120.0, 1, config).unwrap();
+        let mut renderer = PixmapRenderer::new(160, 120).unwrap();
+        aquarium.draw(&mut renderer);
+        // The top of the water is `palette.waterTop`, #005c97.
+        let top = renderer.pixmap().pixel(80, 0).unwrap();
+        let close = |channel: u8, expected: u8| channel.abs_diff(expected) <= 2;
+        assert!(close(top.red(), 0x00) && close(top.green(), 0x5c) && close(top.blue(), 0x97) && top.alpha() == 255, "{:?}", top);
+        let path = std::env::temp_dir().join(format!("aquarium-headless-{}.png", std::process::id()));
+        renderer.save_png(&path).unwrap();
+        let loaded = Pixmap::load_png(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.data(), renderer.pixmap().data());
+    }
+}
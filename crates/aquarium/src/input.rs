@@ -0,0 +1,141 @@
+// crates/aquarium/src/input.rs
+
+/// The kind of device behind a pointer, as in `PointerEvent.pointerType`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerKind { Mouse, Pen, Touch }
+impl PointerKind {
+    /// Reads a `pointerType`; anything unknown is treated as a mouse.
+    pub fn parse(pointer_type: &str) -> Self {
+        match pointer_type { "touch" => PointerKind::Touch, "pen" => PointerKind::Pen, _ => PointerKind::Mouse }
+    }
+}
+
+/// One pointer event, in CSS pixels. `button` is `PointerEvent.button` (`0` primary, `2` secondary, `-1` none) and
+/// `pressure` runs from `0` to `1`, as in the event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointerInput { pub id: i32, pub kind: PointerKind, pub x: f64, pub y: f64, pub button: i16, pub pressure: f64 }
+impl PointerInput {
+    /// The primary mouse button at `(x, y)`.
+    pub fn mouse(x: f64, y: f64) -> Self { Self { id: 1, kind: PointerKind::Mouse, x, y, button: 0, pressure: 0.5 } }
+}
+
+/// What a press turned out to be once recognised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    /// A primary button click, or a quick touch or pen tap.
+    Tap,
+    /// A secondary button click, such as a right click or a pen's barrel button.
+    SecondaryTap,
+    /// A touch or pen held still, the touch equivalent of a right click.
+    LongPress,
+}
+
+/// A recognised gesture, where it happened and the firmest pressure seen during the press.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Recognized { pub gesture: Gesture, pub x: f64, pub y: f64, pub pressure: f64 }
+
+/// What a key does in the tank.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyCommand {
+    /// Drop food at a random spot.
+    Feed,
+    AddFish,
+    /// Pause or resume the animation.
+    Pause,
+    /// Move the feeding spot by the given number of steps across and down.
+    Move(f64, f64),
+    /// Drop food at the feeding spot.
+    Drop,
+    /// Knock on the glass at the feeding spot.
+    Tap,
+}
+impl KeyCommand {
+    /// The command bound to a `KeyboardEvent.key`, if any.
+    pub fn parse(key: &str) -> Option<Self> {
+        Some(match key {
+            "f" | "F" => KeyCommand::Feed, "n" | "N" => KeyCommand::AddFish, " " => KeyCommand::Pause,
+            "Enter" => KeyCommand::Drop, "t" | "T" => KeyCommand::Tap,
+            "ArrowLeft" => KeyCommand::Move(-1.0, 0.0), "ArrowRight" => KeyCommand::Move(1.0, 0.0),
+            "ArrowUp" => KeyCommand::Move(0.0, -1.0), "ArrowDown" => KeyCommand::Move(0.0, 1.0),
+            _ => return None,
+        })
+    }
+}
+
+/// Distance in pixels a press may wander and still count as a tap or long press.
+const TAP_SLOP: f64 = 10.0;
+
+/// A pointer that is pressed down, and what has become of the press so far.
+struct Press {
+    id: i32, kind: PointerKind, button: i16, start: (f64, f64), pressure: f64, held_for: f64,
+    /// Moved beyond `TAP_SLOP`, already fired as a long press, or taken over by something else (a fish picked up,
+    /// say): in any case it will not turn into another gesture.
+    spent: bool,
+}
+
+/// Turns raw pointer presses into gestures, for any number of pointers at once, so several fingers can each tap.
+#[derive(Default)]
+pub struct Gestures { presses: Vec<Press> }
+impl Gestures {
+    /// Starts tracking a press. A `claimed` press is followed but never recognised as a gesture.
+    pub fn press(&mut self, input: PointerInput, claimed: bool) {
+        self.cancel(input.id);
+        self.presses.push(Press { id: input.id, kind: input.kind, button: input.button, start: (input.x, input.y), pressure: input.pressure, held_for: 0.0, spent: claimed });
+    }
+    pub fn drag(&mut self, input: PointerInput) {
+        let Some(press) = self.presses.iter_mut().find(|p| p.id == input.id) else { return; };
+        press.pressure = press.pressure.max(input.pressure);
+        if (input.x - press.start.0).powi(2) + (input.y - press.start.1).powi(2) > TAP_SLOP * TAP_SLOP { press.spent = true; }
+    }
+    /// Ends a press, recognising it as a tap if it was short and stayed put.
+    pub fn release(&mut self, input: PointerInput) -> Option<Recognized> {
+        self.drag(input);
+        let i = self.presses.iter().position(|p| p.id == input.id)?;
+        let press = self.presses.swap_remove(i);
+        if press.spent { return None; }
+        let gesture = match press.button { 0 => Gesture::Tap, 2 => Gesture::SecondaryTap, _ => return None };
+        Some(Recognized { gesture, x: press.start.0, y: press.start.1, pressure: press.pressure })
+    }
+    /// Whether any press is still on its way to becoming a long press.
+    pub fn is_pending(&self) -> bool { self.presses.iter().any(|p| !p.spent && p.kind != PointerKind::Mouse && p.button == 0) }
+    /// Forgets a press without recognising anything, as for `pointercancel`.
+    pub fn cancel(&mut self, id: i32) { self.presses.retain(|p| p.id != id); }
+    /// Ages the presses by `dt` seconds, appending a long press for each touch or pen press held still for
+    /// `long_press` seconds.
+    pub fn advance(&mut self, dt: f64, long_press: f64, recognized: &mut Vec<Recognized>) {
+        for press in &mut self.presses {
+            press.held_for += dt;
+            if press.spent || press.kind == PointerKind::Mouse || press.button != 0 || press.held_for < long_press { continue; }
+            press.spent = true;
+            recognized.push(Recognized { gesture: Gesture::LongPress, x: press.start.0, y: press.start.1, pressure: press.pressure });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_taps_long_presses_and_drags_per_pointer() {
+        let touch = |id, x, y| PointerInput { id, kind: PointerKind::Touch, x, y, button: 0, pressure: 0.4 };
+        let mut gestures = Gestures::default();
+        let mut long_presses = Vec::new();
+        // Two fingers at once: one taps, one is held until it long-presses.
+        gestures.press(touch(1, 10.0, 10.0), false);
+        gestures.press(touch(2, 100.0, 10.0), false);
+        gestures.advance(0.2, 0.5, &mut long_presses);
+        assert_eq!(gestures.release(touch(1, 12.0, 11.0)).map(|r| (r.gesture, r.x)), Some((Gesture::Tap, 10.0)));
+        gestures.advance(0.4, 0.5, &mut long_presses);
+        assert_eq!(long_presses.iter().map(|r| (r.gesture, r.x)).collect::<Vec<_>>(), [(Gesture::LongPress, 100.0)]);
+        assert_eq!(gestures.release(touch(2, 100.0, 10.0)), None);
+        // A drag is no tap, and a mouse never long-presses.
+        gestures.press(touch(3, 0.0, 0.0), false);
+        gestures.drag(touch(3, 30.0, 0.0));
+        assert_eq!(gestures.release(touch(3, 30.0, 0.0)), None);
+        gestures.press(PointerInput { button: 2, ..PointerInput::mouse(5.0, 5.0) }, false);
+        gestures.advance(2.0, 0.5, &mut long_presses);
+        assert_eq!(long_presses.len(), 1);
+        assert_eq!(gestures.release(PointerInput { button: 2, ..PointerInput::mouse(5.0, 5.0) }).map(|r| r.gesture), Some(Gesture::SecondaryTap));
+    }
+}
@@ -1,114 +1,166 @@
 // crates/aquarium/src/lib.rs
-use std::cell::RefCell;
-use std::rc::Rc;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 use rand::prelude::*;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 
+pub mod canvas;
+pub mod config;
+pub mod events;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
+pub mod input;
+pub mod obstacles;
+pub mod renderer;
+pub mod snapshot;
+pub mod spatial;
+pub mod species;
+pub mod web;
+
+use config::{Action, AquariumConfig, LifecycleConfig, Motion, Palette, Temperament};
+use events::{CrabState, DeathCause, EventLog, SpawnCause, TankEvent};
+use input::{Gesture, Gestures, KeyCommand, PointerInput, Recognized};
+use obstacles::Obstacle;
+use renderer::{parse_css_color, Renderer};
+use snapshot::{BubbleSnapshot, Counts, CrabSnapshot, EntitySnapshot, FishSnapshot, FishState, FoodSnapshot};
+use spatial::SpatialGrid;
+use species::{Pose, Species};
+
+/// Length of one simulation step in seconds. `tick` runs as many of these as fit into the elapsed wall-clock time.
+const FIXED_STEP: f64 = 1.0 / 60.0;
+/// Upper bound on steps per `tick`, so a long stall (e.g. a throttled background tab) doesn't replay minutes of simulation.
+const MAX_STEPS_PER_TICK: u32 = 10;
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 { from + (to - from) * t }
+fn god_ray_count(width: f64, density: f64) -> usize { if density <= 0.0 { 0 } else { (width * density / 1000.0).clamp(3.0, 20.0).round() as usize } }
+fn castle_scale_for(height: f64) -> f64 { (height / 1000.0).max(0.5) * 1.5 }
+
 // --- GodRay System ---
 struct GodRay {
     x: f64, top_width: f64, bottom_width: f64, length: f64,
     blur: f64, life: f64, max_life: f64,
 }
 impl GodRay {
-    fn reset(&mut self, canvas_width: f64, canvas_height: f64) {
-        let mut rng = rand::thread_rng();
+    fn reset(&mut self, canvas_width: f64, canvas_height: f64, rng: &mut StdRng) {
         self.x = rng.gen_range(-canvas_width * 0.2..canvas_width * 1.2);
         self.top_width = rng.gen_range(20.0..150.0);
         self.bottom_width = rng.gen_range(0.0..self.top_width * 0.3);
         self.length = rng.gen_range(canvas_height * 0.5..canvas_height * 1.2);
         self.blur = rng.gen_range(10.0..25.0);
-        self.max_life = rng.gen_range(1200.0..1800.0);
+        self.max_life = rng.gen_range(20.0..30.0);
         self.life = 0.0;
     }
-    fn new(canvas_width: f64, canvas_height: f64) -> Self {
+    fn new(canvas_width: f64, canvas_height: f64, rng: &mut StdRng) -> Self {
         let mut ray = Self { x: 0.0, top_width: 0.0, bottom_width: 0.0, length: 0.0, blur: 0.0, life: 0.0, max_life: 0.0 };
-        ray.reset(canvas_width, canvas_height);
+        ray.reset(canvas_width, canvas_height, rng);
         ray
     }
-    fn update(&mut self) { self.life += 1.0; }
-    fn draw(&self, ctx: &CanvasRenderingContext2d) {
+    fn update(&mut self, dt: f64) { self.life += dt; }
+    /// A `steady` ray doesn't fade in and out; it glows at its average brightness over a lifetime.
+    fn draw(&self, ctx: &mut dyn Renderer, [r, g, b, a]: [f32; 4], steady: bool) {
         let life_progress = self.life / self.max_life;
-        let alpha = js_sys::Math::sin(life_progress * std::f64::consts::PI) * 0.15;
+        let fade = if steady { std::f64::consts::FRAC_2_PI } else { (life_progress * std::f64::consts::PI).sin() };
+        let alpha = fade * 0.15 * a as f64;
         if alpha <= 0.0 { return; }
         ctx.save();
-        ctx.set_filter(&format!("blur({}px)", self.blur));
-        ctx.set_fill_style(&JsValue::from_str(&format!("rgba(210, 230, 255, {})", alpha)));
+        ctx.set_blur(self.blur);
+        ctx.set_fill_color(&format!("rgba({}, {}, {}, {})", (r * 255.0).round(), (g * 255.0).round(), (b * 255.0).round(), alpha));
         let y_offset = -50.0;
-        ctx.translate(self.x, 0.0).unwrap();
-        ctx.rotate(-15.0f64.to_radians()).unwrap();
+        ctx.translate(self.x, 0.0);
+        ctx.rotate(-15.0f64.to_radians());
         ctx.begin_path(); ctx.move_to(-self.top_width / 2.0, y_offset); ctx.line_to(self.top_width / 2.0, y_offset); ctx.line_to(self.bottom_width / 2.0, y_offset + self.length); ctx.line_to(-self.bottom_width / 2.0, y_offset + self.length); ctx.close_path(); ctx.fill(); ctx.restore();
     }
 }
 
 // --- Bubble System ---
 const BUBBLE_SOURCES: &[(f64, f64)] = &[(-80.0, -30.0), (35.0, -135.0)];
+/// Where new bubbles appear: the castle's openings when it is shown, otherwise anywhere along the sand.
+#[derive(Clone, Copy)]
+enum BubbleSpawn { Castle { center_x: f64, base_y: f64, scale: f64 }, Seafloor { width: f64, floor_y: f64 } }
+impl BubbleSpawn {
+    fn pick(self, rng: &mut StdRng) -> (f64, f64) {
+        match self {
+            BubbleSpawn::Castle { center_x, base_y, scale } => {
+                let source = BUBBLE_SOURCES.choose(rng).unwrap();
+                (center_x + source.0 * scale + rng.gen_range(-5.0..5.0), base_y + source.1 * scale)
+            }
+            BubbleSpawn::Seafloor { width, floor_y } => (rng.gen_range(0.0..width.max(1.0)), floor_y),
+        }
+    }
+}
 struct Bubble {
-    x: f64, y: f64, original_x: f64, size: f64, speed_y: f64, wobble_angle: f64,
+    x: f64, y: f64, prev_x: f64, prev_y: f64, original_x: f64, size: f64, speed_y: f64, wobble_angle: f64,
 }
 impl Bubble {
-    fn new(castle_center_x: f64, castle_base_y: f64, castle_scale: f64) -> Self {
-        let mut bubble = Bubble { x: 0.0, y: 0.0, original_x: 0.0, size: 0.0, speed_y: 0.0, wobble_angle: 0.0 };
-        bubble.reset(castle_center_x, castle_base_y, castle_scale);
+    fn new(spawn: BubbleSpawn, rng: &mut StdRng) -> Self {
+        let mut bubble = Bubble { x: 0.0, y: 0.0, prev_x: 0.0, prev_y: 0.0, original_x: 0.0, size: 0.0, speed_y: 0.0, wobble_angle: 0.0 };
+        bubble.reset(spawn, rng);
         bubble
     }
-    fn reset(&mut self, castle_center_x: f64, castle_base_y: f64, castle_scale: f64) {
-        let mut rng = rand::thread_rng();
-        let source = BUBBLE_SOURCES.choose(&mut rng).unwrap();
-        self.original_x = castle_center_x + source.0 * castle_scale + rng.gen_range(-5.0..5.0);
-        self.y = castle_base_y + source.1 * castle_scale;
+    fn reset(&mut self, spawn: BubbleSpawn, rng: &mut StdRng) {
+        (self.original_x, self.y) = spawn.pick(rng);
         self.size = rng.gen_range(1.0..5.0);
-        self.speed_y = rng.gen_range(0.5..1.5);
+        self.speed_y = rng.gen_range(30.0..90.0);
         self.wobble_angle = rng.gen_range(0.0..std::f64::consts::PI * 2.0);
+        self.x = self.original_x + self.wobble_angle.sin() * self.size * 0.5;
+        self.prev_x = self.x; self.prev_y = self.y;
     }
-    fn update(&mut self) {
-        self.y -= self.speed_y; self.wobble_angle += 0.05;
+    fn update(&mut self, dt: f64) {
+        self.prev_x = self.x; self.prev_y = self.y;
+        self.y -= self.speed_y * dt; self.wobble_angle += 3.0 * dt;
         self.x = self.original_x + self.wobble_angle.sin() * self.size * 0.5;
     }
-    fn draw(&self, ctx: &CanvasRenderingContext2d) {
-        ctx.begin_path(); ctx.arc(self.x, self.y, self.size, 0.0, std::f64::consts::PI * 2.0).unwrap();
-        ctx.set_fill_style(&JsValue::from_str("rgba(220, 235, 255, 0.6)")); ctx.fill();
-        ctx.set_stroke_style(&JsValue::from_str("rgba(255, 255, 255, 0.8)")); ctx.set_line_width(1.0); ctx.stroke();
+    fn snapshot(&self) -> BubbleSnapshot { BubbleSnapshot { x: self.x, y: self.y, vy: -self.speed_y, size: self.size } }
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f64, palette: &Palette) {
+        ctx.begin_path(); ctx.arc(lerp(self.prev_x, self.x, alpha), lerp(self.prev_y, self.y, alpha), self.size, 0.0, std::f64::consts::PI * 2.0);
+        ctx.set_fill_color(&palette.bubble); ctx.fill();
+        ctx.set_stroke_color(&palette.bubble_outline); ctx.set_line_width(1.0); ctx.stroke();
     }
 }
 
 // --- Crab System ---
-enum CrabState { Walking, Waiting }
+const CRAB_SPEED: f64 = 30.0;
 struct Crab {
-    x: f64, y: f64, size: f64, direction: f64, state: CrabState, state_timer: i32,
+    id: u32, x: f64, y: f64, prev_x: f64, prev_y: f64, size: f64, direction: f64, state: CrabState, state_timer: f64,
+    /// Set when the crab is surplus to the population target: it walks off the nearer edge and is then removed.
+    leaving: bool,
 }
 impl Crab {
-    fn new(canvas_width: f64, canvas_height: f64) -> Self {
-        let mut rng = rand::thread_rng();
+    fn new(canvas_width: f64, canvas_height: f64, rng: &mut StdRng) -> Self {
+        let (x, y) = (rng.gen_range(0.0..canvas_width), canvas_height * 0.9);
         Self {
-            x: rng.gen_range(0.0..canvas_width), y: canvas_height * 0.9,
+            id: 0, x, y, prev_x: x, prev_y: y,
             size: rng.gen_range(10.0..15.0), direction: if rng.gen_bool(0.5) { 1.0 } else { -1.0 },
-            state: CrabState::Walking, state_timer: rng.gen_range(100..300),
+            state: CrabState::Walking, state_timer: rng.gen_range(1.7..5.0), leaving: false,
         }
     }
-    fn update(&mut self, floor_y: f64, width: f64) {
-        self.state_timer -= 1;
-        if self.state_timer <= 0 {
-            let mut rng = rand::thread_rng();
+    fn is_gone(&self, width: f64) -> bool { self.leaving && (self.x < -self.size * 2.0 || self.x > width + self.size * 2.0) }
+    /// The shell and the legs either side of it.
+    fn hit(&self, x: f64, y: f64) -> bool { (x - self.x).abs() < self.size * 1.5 + PICK_SLACK && (-self.size - PICK_SLACK..=self.size * 1.5).contains(&(y - self.y)) }
+    fn snapshot(&self) -> CrabSnapshot {
+        let vx = if self.state == CrabState::Walking { self.direction * CRAB_SPEED } else { 0.0 };
+        CrabSnapshot { id: self.id, x: self.x, y: self.y, vx, size: self.size, state: self.state, leaving: self.leaving }
+    }
+    fn update(&mut self, floor_y: f64, width: f64, dt: f64, rng: &mut StdRng) {
+        self.prev_x = self.x; self.prev_y = self.y;
+        self.state_timer -= dt;
+        if self.state_timer <= 0.0 {
             match self.state {
-                CrabState::Walking => { self.state = CrabState::Waiting; self.state_timer = rng.gen_range(60..180); }
-                CrabState::Waiting => { self.state = CrabState::Walking; self.state_timer = rng.gen_range(100..300); }
+                CrabState::Walking => { self.state = CrabState::Waiting; self.state_timer = rng.gen_range(1.0..3.0); }
+                CrabState::Waiting => { self.state = CrabState::Walking; self.state_timer = rng.gen_range(1.7..5.0); }
             }
         }
-        if let CrabState::Walking = self.state { self.x += self.direction * 0.5; }
+        if self.leaving { self.state = CrabState::Walking; self.direction = if self.x < width * 0.5 { -1.0 } else { 1.0 }; }
+        if let CrabState::Walking = self.state { self.x += self.direction * CRAB_SPEED * dt; }
         self.y = floor_y - 8.0;
-        if (self.x > width && self.direction > 0.0) || (self.x < 0.0 && self.direction < 0.0) {
-            self.direction *= -1.0; self.state = CrabState::Walking; self.state_timer = rand::thread_rng().gen_range(100..300);
+        if !self.leaving && ((self.x > width && self.direction > 0.0) || (self.x < 0.0 && self.direction < 0.0)) {
+            self.direction *= -1.0; self.state = CrabState::Walking; self.state_timer = rng.gen_range(1.7..5.0);
         }
     }
-    fn draw(&self, ctx: &CanvasRenderingContext2d) {
-        ctx.save(); ctx.translate(self.x, self.y).unwrap();
-        ctx.set_fill_style(&JsValue::from_str("#d14124"));
-        ctx.begin_path(); ctx.arc(0.0, 0.0, self.size, std::f64::consts::PI, 0.0).unwrap(); ctx.close_path(); ctx.fill();
-        ctx.set_stroke_style(&JsValue::from_str("#d14124")); ctx.set_line_width(2.0);
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f64, palette: &Palette) {
+        ctx.save(); ctx.translate(lerp(self.prev_x, self.x, alpha), lerp(self.prev_y, self.y, alpha));
+        ctx.set_fill_color(&palette.crab);
+        ctx.begin_path(); ctx.arc(0.0, 0.0, self.size, std::f64::consts::PI, 0.0); ctx.close_path(); ctx.fill();
+        ctx.set_stroke_color(&palette.crab); ctx.set_line_width(2.0);
         for i in 0..3 {
             let angle = (i as f64 * 0.5) + 0.2;
             ctx.begin_path(); ctx.move_to(-self.size, 0.0); ctx.line_to(-self.size * 1.5, angle * 10.0); ctx.stroke();
@@ -119,36 +171,287 @@ impl Crab {
 }
 
 // --- Fish and Food System ---
-#[derive(Clone)] struct Food { x: f64, y: f64, vy: f64, }
+const FOOD_GRAVITY: f64 = 25.2;
+/// Fraction of a sinking pellet's speed that survives one second of water drag.
+const FOOD_DRAG: f64 = 0.547;
+/// Rate at which steering closes the gap between a fish's desired and current velocity, per second.
+const STEERING_RESPONSE: f64 = 60.0;
+/// Maximum change of the wander angle, in radians per second.
+const WANDER_JITTER: f64 = 18.0;
+/// Extra speed, in pixels per second, a fish gains when it is fully focused on food.
+const FEEDING_SPEED_BOOST: f64 = 30.0;
+/// Extra speed of a predator closing in on its prey, and of prey running for their lives.
+const HUNT_SPEED_BOOST: f64 = 45.0;
+const FLEE_SPEED_BOOST: f64 = 36.0;
+/// Distance within which a hungry predator picks a target, in pixels.
+const HUNT_RADIUS: f64 = 260.0;
+/// Prey must be smaller than this fraction of a predator's size to interest it.
+const PREY_SIZE_RATIO: f64 = 0.8;
+/// Rate at which a fish inside its depth band sheds vertical speed, per second.
+const LEVEL_DAMPING: f64 = 1.5;
+/// Cell size of the food index, in pixels; most closest-food lookups end within a few cells.
+const FOOD_GRID_CELL: f64 = 64.0;
+/// Hunger below which a fish is sated and ignores food; appetite then builds up to full over `APPETITE_RAMP`.
+const SATIATED: f64 = 0.2;
+const APPETITE_RAMP: f64 = 0.3;
+/// Hunger one pellet takes away.
+const MEAL: f64 = 0.35;
+/// Seconds a fish that died of old age takes to fade out.
+const DEATH_FADE: f64 = 4.0;
+/// Speed at which a dead fish drifts up towards the surface, in pixels per second.
+const DEATH_FLOAT_SPEED: f64 = 6.0;
+/// Fry hatch at this fraction of their adult size and grow up over `FRY_SECONDS`.
+const FRY_SCALE: f64 = 0.4;
+const FRY_SECONDS: f64 = 60.0;
+/// Hunger below which a grown fish is in the mood to breed.
+const WELL_FED: f64 = 0.4;
+/// Chance per second that two fish ready to breed, close together, spawn fry, before crowding is accounted for.
+const BREEDING_RATE: f64 = 0.25;
+/// Tail beats per second at rest, plus extra per body length per second of speed and while darting.
+const TAIL_BEAT_HZ: f64 = 0.6;
+const TAIL_BEAT_PER_BODY_LENGTH: f64 = 0.7;
+const BURST_BEAT_HZ: f64 = 2.5;
+/// Pectoral fin strokes per second.
+const FIN_BEAT_HZ: f64 = 1.6;
+/// Tail swing as a fraction of size at rest, extra at cruising speed and extra at the start of a dart.
+const TAIL_SWING: f64 = 0.08;
+const TAIL_SWING_AT_SPEED: f64 = 0.12;
+const BURST_SWING: f64 = 0.25;
+/// Seek weight above which a fish darts for food, and how long the dart's flurry lasts.
+const BURST_THRESHOLD: f64 = 0.5;
+const BURST_SECONDS: f64 = 0.6;
+/// How fast the body turns towards the swimming direction (radians per second), how quickly it leans into a turn,
+/// how fast it rolls over when turning around (facings per second) and how much a full lean squashes it.
+const TURN_RATE: f64 = 4.0;
+const BANK_RESPONSE: f64 = 8.0;
+const ROLL_RATE: f64 = 3.0;
+const BANK_SQUASH: f64 = 0.25;
+/// How long a startled fish bolts, how close a curious one comes (in body sizes) and how strongly a still pointer
+/// right next to a fish draws it in.
+const STARTLE_SECONDS: f64 = 1.5;
+const CURIOUS_DISTANCE: f64 = 3.0;
+const CURIOSITY: f64 = 0.8;
+/// Rate at which the measured pointer speed follows the pointer, per second.
+const POINTER_SMOOTHING: f64 = 12.0;
+/// Extra margin in pixels around a fish's body that still picks it up, and the fastest a fish can be thrown.
+const PICK_SLACK: f64 = 4.0;
+const MAX_THROW_SPEED: f64 = 600.0;
+/// How fast bubbles rise, and how fast and sharply fish swim and turn, in calm mode, relative to full motion.
+const CALM_BUBBLE_PACE: f64 = 0.3;
+const CALM_FISH_PACE: f64 = 0.5;
+/// Distance in pixels an arrow key moves the feeding spot, and the size of its ring.
+const RETICLE_STEP: f64 = 25.0;
+const RETICLE_RADIUS: f64 = 12.0;
+/// Extra food pellets per unit of pressure above a normal press.
+const PELLETS_PER_PRESSURE: f64 = 6.0;
+/// Rate at which a fish faster than its top speed slows back down to it, per second.
+const COAST_DRAG: f64 = 3.0;
+/// How far ahead, in seconds of swimming, a fish's feelers reach for obstacles.
+const FEELER_LOOKAHEAD: f64 = 1.2;
+/// Angle between the center feeler and each side feeler, and the side feelers' length relative to the center one.
+const FEELER_ANGLE: f64 = 0.5;
+const SIDE_FEELER_LENGTH: f64 = 0.6;
+/// Distance between two fish, relative to their size, close enough to breed.
+const MATE_DISTANCE: f64 = 2.5;
+/// Distance within which fish see each other as schoolmates, in pixels.
+const NEIGHBOR_RADIUS: f64 = 70.0;
+/// Schoolmates closer than this many body lengths push each other apart.
+const SEPARATION_DISTANCE: f64 = 2.0;
+/// Position and velocity of a fish as its schoolmates saw it at the start of the step.
+#[derive(Clone, Copy)] struct Boid { x: f64, y: f64, vx: f64, vy: f64 }
+/// What a fish notices around it at the start of a step.
+struct Senses<'a> {
+    closest_food: Option<(f64, f64, f64)>,
+    /// Schoolmates within `NEIGHBOR_RADIUS`.
+    neighbours: &'a [Boid],
+    /// Predators within the flee radius that could eat this fish.
+    threats: &'a [Boid],
+    /// For a hungry predator, the prey it is chasing.
+    prey: Option<Boid>,
+    /// Scene geometry to steer around.
+    obstacles: &'a [Obstacle],
+    /// A pointer worth a closer look, as `(x, y, weight)`.
+    interest: Option<(f64, f64, f64)>,
+}
+#[derive(Clone)] struct Food { x: f64, y: f64, prev_y: f64, vy: f64, }
 #[derive(Clone)]
 struct Fish {
-    x: f64, y: f64, vx: f64, vy: f64, ax: f64, ay: f64,
-    size: f64, color: String, wander_angle: f64, max_speed: f64, max_force: f64,
+    /// Unique in the tank, handed out by `Aquarium::spawn_fish`.
+    id: u32,
+    x: f64, y: f64, prev_x: f64, prev_y: f64, vx: f64, vy: f64, ax: f64, ay: f64,
+    species: Species, size: f64, color: String, accent: String, wander_angle: f64, max_speed: f64, max_force: f64,
+    /// `0.0` just fed to `1.0` starving.
+    hunger: f64,
+    /// Size when grown up. Fry grow into it over `FRY_SECONDS`; meals grow `size` further, up to
+    /// `adult_size * LifecycleConfig::max_growth`.
+    adult_size: f64,
+    age: f64, lifespan: f64,
+    /// Seconds until the fish can breed again.
+    breed_cooldown: f64,
+    /// Seconds since the fish died, while it fades out.
+    dead_for: Option<f64>,
+    /// Set when the fish is surplus to the population target: it swims out through the nearer side and is then removed.
+    leaving: bool,
+    /// Where the fish was last startled from, while it is still bolting.
+    startle: Option<Startle>,
+    /// Being dragged around by the pointer.
+    held: bool,
+    swim: Swim,
+}
+#[derive(Clone, Copy)]
+struct Startle { x: f64, y: f64, left: f64 }
+/// The swimming stroke and body attitude, which trail the fish's velocity so turns and bursts look smooth.
+#[derive(Clone)]
+struct Swim {
+    /// Phases of the tail beat and of the pectoral fins' paddling, in radians.
+    phase: f64, fin_phase: f64,
+    /// Direction the body points, turning towards the velocity at a limited rate.
+    heading: f64,
+    /// `1.0` with the back up facing right, `-1.0` mirrored facing left; in between while rolling over in a turn.
+    facing: f64,
+    /// Lean into the current turn, `-1.0` to `1.0`.
+    bank: f64,
+    /// Left-over strength of a dart towards food, decaying from `1.0`.
+    burst: f64,
+    seeking: bool,
+}
+impl Default for Swim {
+    fn default() -> Self { Self { phase: 0.0, fin_phase: 0.0, heading: 0.0, facing: 1.0, bank: 0.0, burst: 0.0, seeking: false } }
+}
+impl Swim {
+    /// Advances the stroke for a fish moving at `(vx, vy)`; `seek_w` is how strongly it is going for food.
+    /// `pace` scales how quickly the fish may turn.
+    fn update(&mut self, vx: f64, vy: f64, size: f64, seek_w: f64, pace: f64, dt: f64) {
+        let speed = (vx * vx + vy * vy).sqrt();
+        let seeking = seek_w > BURST_THRESHOLD;
+        if seeking && !self.seeking { self.burst = 1.0; }
+        self.seeking = seeking; self.burst = (self.burst - dt / BURST_SECONDS).max(0.0);
+        let beat = TAIL_BEAT_HZ + TAIL_BEAT_PER_BODY_LENGTH * speed / (size * 2.0) + self.burst * BURST_BEAT_HZ;
+        self.phase = (self.phase + beat * dt * std::f64::consts::TAU) % std::f64::consts::TAU;
+        self.fin_phase = (self.fin_phase + (FIN_BEAT_HZ + self.burst * BURST_BEAT_HZ) * dt * std::f64::consts::TAU) % std::f64::consts::TAU;
+        let mut turn = 0.0;
+        if speed > 1.0 {
+            let off = (vy.atan2(vx) - self.heading + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI;
+            turn = off.clamp(-TURN_RATE * pace * dt, TURN_RATE * pace * dt);
+            self.heading += turn;
+        }
+        self.bank += ((turn / (TURN_RATE * dt)).clamp(-1.0, 1.0) - self.bank) * (1.0 - (-BANK_RESPONSE * dt).exp());
+        let facing = if self.heading.cos() >= 0.0 { 1.0 } else { -1.0 };
+        self.facing += (facing - self.facing).clamp(-ROLL_RATE * dt, ROLL_RATE * dt);
+    }
+    /// The body bend for a fish swimming at `speed_ratio` of its cruising speed: faster fish beat their tails wider.
+    fn pose(&self, speed_ratio: f64) -> Pose {
+        Pose { phase: self.phase, amplitude: TAIL_SWING + TAIL_SWING_AT_SPEED * speed_ratio.min(1.5) + BURST_SWING * self.burst, fin: self.fin_phase.sin() }
+    }
 }
 impl Fish {
-    fn new(canvas_width: f64, canvas_height: f64) -> Self {
-        let mut rng = rand::thread_rng(); let size = rng.gen_range(10.0..18.0);
+    /// A fish of one of the configured species, somewhere in that species' depth band.
+    fn random(config: &AquariumConfig, canvas_width: f64, canvas_height: f64, rng: &mut StdRng) -> Self {
+        let species = *config.species.choose(rng).unwrap_or(&Species::Tetra);
+        Self::new(species, canvas_width, canvas_height, config, rng)
+    }
+    /// Fish start somewhere between peckish and hungry and up to halfway through their life, so a new tank neither
+    /// mobs the first pellet nor dies off all at once. `palette.fish` overrides the species' body colors.
+    fn new(species: Species, canvas_width: f64, canvas_height: f64, config: &AquariumConfig, rng: &mut StdRng) -> Self {
+        let colors = &config.palette.fish;
+        let spec = species.spec();
+        let size = rng.gen_range(spec.size.0..spec.size.1);
+        let (x, y) = (rng.gen_range(0.0..canvas_width), rng.gen_range(spec.depth.0..spec.depth.1) * canvas_height);
+        let (body, accent) = spec.colors.choose(rng).unwrap();
+        let color = colors.choose(rng).cloned().unwrap_or_else(|| body.to_string());
         Self {
-            x: rng.gen_range(0.0..canvas_width), y: rng.gen_range(0.0..canvas_height * 0.8), vx: 0.0, vy: 0.0, ax: 0.0, ay: 0.0, size,
-            color: format!("hsl({}, 80%, 70%)", rng.gen_range(0..360)), wander_angle: rng.gen_range(0.0..std::f64::consts::PI * 2.0),
-            max_speed: rng.gen_range(0.3..0.6), max_force: rng.gen_range(0.01..0.03),
+            id: 0, x, y, prev_x: x, prev_y: y, vx: 0.0, vy: 0.0, ax: 0.0, ay: 0.0, species, size, color, accent: accent.to_string(),
+            wander_angle: rng.gen_range(0.0..std::f64::consts::PI * 2.0),
+            max_speed: rng.gen_range(spec.max_speed.0..spec.max_speed.1), max_force: rng.gen_range(spec.max_force.0..spec.max_force.1),
+            hunger: rng.gen_range(0.3..0.8), adult_size: size, age: 0.0, lifespan: 0.0, breed_cooldown: 0.0, dead_for: None, leaving: false, startle: None, held: false, swim: Swim::default(),
+        }.with_lifespan(config.lifecycle.lifespan_seconds, rng)
+    }
+    fn with_lifespan(mut self, average: f64, rng: &mut StdRng) -> Self {
+        self.lifespan = average * rng.gen_range(0.75..1.25); self.age = self.lifespan * rng.gen_range(0.0..0.5); self
+    }
+    /// A fry of `a` and `b`: color, size and pace are blended from the parents with a little variation.
+    fn fry(a: &Fish, b: &Fish, lifecycle: &LifecycleConfig, rng: &mut StdRng) -> Self {
+        let spec = a.species.spec();
+        let blend = |x: f64, y: f64, rng: &mut StdRng| (x + y) * 0.5 * rng.gen_range(0.92..1.08);
+        let adult_size = blend(a.adult_size, b.adult_size, rng).clamp(spec.size.0 * 0.8, spec.size.1 * 1.2);
+        let (x, y) = ((a.x + b.x) * 0.5 + rng.gen_range(-4.0..4.0), (a.y + b.y) * 0.5 + rng.gen_range(-4.0..4.0));
+        let color = match (parse_css_color(&a.color), parse_css_color(&b.color)) {
+            (Some(ca), Some(cb)) => {
+                let channel = |i: usize, rng: &mut StdRng| ((ca[i] + cb[i]) as f64 * 0.5 * 255.0 + rng.gen_range(-12.0..12.0)).clamp(0.0, 255.0).round();
+                format!("rgb({}, {}, {})", channel(0, rng), channel(1, rng), channel(2, rng))
+            }
+            _ => a.color.clone(),
+        };
+        Self {
+            id: 0, x, y, prev_x: x, prev_y: y, vx: 0.0, vy: 0.0, ax: 0.0, ay: 0.0, species: a.species, size: adult_size * FRY_SCALE, color,
+            accent: if rng.gen_bool(0.5) { a.accent.clone() } else { b.accent.clone() }, wander_angle: rng.gen_range(0.0..std::f64::consts::PI * 2.0),
+            max_speed: blend(a.max_speed, b.max_speed, rng), max_force: blend(a.max_force, b.max_force, rng),
+            hunger: 0.5, adult_size, age: 0.0, lifespan: lifecycle.lifespan_seconds * rng.gen_range(0.75..1.25), breed_cooldown: 0.0, dead_for: None, leaving: false, startle: None, held: false, swim: Swim::default(),
+        }
+    }
+    fn is_alive(&self) -> bool { self.dead_for.is_none() }
+    fn is_adult(&self) -> bool { self.size >= self.adult_size }
+    /// Grown, well fed, rested, not on its way out and not held.
+    fn can_breed(&self) -> bool { self.is_alive() && !self.leaving && !self.held && self.is_adult() && self.hunger < WELL_FED && self.breed_cooldown <= 0.0 }
+    /// How much the fish cares about food, from `0.0` when sated to `1.0` when properly hungry.
+    fn appetite(&self) -> f64 { ((self.hunger - SATIATED) / APPETITE_RAMP).clamp(0.0, 1.0) }
+    /// Gets hungrier and older; past its lifespan the fish dies if `natural_death` is on.
+    fn live(&mut self, lifecycle: &LifecycleConfig, dt: f64) {
+        match &mut self.dead_for {
+            Some(dead_for) => *dead_for += dt,
+            None => {
+                self.hunger = (self.hunger + dt / lifecycle.hunger_seconds).min(1.0);
+                self.age += dt; self.breed_cooldown -= dt;
+                if !self.is_adult() { self.size = (self.size + self.adult_size * (1.0 - FRY_SCALE) * dt / FRY_SECONDS).min(self.adult_size); }
+                if lifecycle.natural_death && self.age > self.lifespan { self.dead_for = Some(0.0); }
+            }
         }
     }
+    fn eat(&mut self, lifecycle: &LifecycleConfig) {
+        self.hunger = (self.hunger - MEAL).max(0.0);
+        self.size = (self.size + self.adult_size * lifecycle.growth_per_meal).min(self.adult_size * lifecycle.max_growth);
+    }
+    fn boid(&self) -> Boid { Boid { x: self.x, y: self.y, vx: self.vx, vy: self.vy } }
+    fn snapshot(&self) -> FishSnapshot {
+        let state = if !self.is_alive() { FishState::Dead } else if self.held { FishState::Held } else if self.leaving { FishState::Leaving }
+            else if self.startle.is_some() { FishState::Startled } else if self.swim.seeking { FishState::Feeding } else { FishState::Swimming };
+        FishSnapshot {
+            id: self.id, species: self.species, x: self.x, y: self.y, vx: self.vx, vy: self.vy, size: self.size, state,
+            hunger: self.hunger, age: self.age, adult: self.is_adult(), color: self.color.clone(),
+        }
+    }
+    fn is_gone(&self, width: f64) -> bool {
+        self.dead_for.is_some_and(|t| t >= DEATH_FADE) || (self.leaving && (self.x < -self.size * 2.0 || self.x > width + self.size * 2.0))
+    }
+    fn place(&mut self, x: f64, y: f64) { self.x = x; self.y = y; self.prev_x = x; self.prev_y = y; }
     fn apply_force(&mut self, fx: f64, fy: f64) { self.ax += fx; self.ay += fy; }
     fn get_seek_force(&self, closest_food: Option<(f64, f64, f64)>, width: f64, height: f64) -> (f64, f64, f64) {
         if let Some((dist_sq, target_x, target_y)) = closest_food {
             let canvas_diagonal = (width.powi(2) + height.powi(2)).sqrt(); let dist = dist_sq.sqrt();
-            let urgency = (1.0 - (dist / canvas_diagonal)).powf(2.0).clamp(0.0, 1.0);
-            let mut desired_x = target_x - self.x; let mut desired_y = target_y - self.y;
-            let d_mag = (desired_x * desired_x + desired_y * desired_y).sqrt();
-            if d_mag > 0.0 { desired_x = (desired_x / d_mag) * self.max_speed; desired_y = (desired_y / d_mag) * self.max_speed; }
-            let steer_x = desired_x - self.vx; let steer_y = desired_y - self.vy;
-            let steer_mag_sq = steer_x * steer_x + steer_y * steer_y;
-            if steer_mag_sq > self.max_force * self.max_force { let mag = steer_mag_sq.sqrt(); return ((steer_x / mag) * self.max_force, (steer_y / mag) * self.max_force, urgency); }
+            let urgency = (1.0 - (dist / canvas_diagonal)).powf(2.0).clamp(0.0, 1.0) * self.appetite();
+            let (steer_x, steer_y) = self.steer_towards(target_x, target_y);
             return (steer_x, steer_y, urgency);
         }
         (0.0, 0.0, 0.0)
     }
+    fn steer_towards(&self, target_x: f64, target_y: f64) -> (f64, f64) { self.steer_along(target_x - self.x, target_y - self.y) }
+    /// Steering force that turns the fish to swim at full speed in direction `(desired_x, desired_y)`.
+    fn steer_along(&self, desired_x: f64, desired_y: f64) -> (f64, f64) { self.steer_at(desired_x, desired_y, self.max_speed) }
+    /// Like `steer_along`, aiming for `speed` instead of the cruising speed.
+    fn steer_at(&self, mut desired_x: f64, mut desired_y: f64, speed: f64) -> (f64, f64) {
+        let d_mag = (desired_x * desired_x + desired_y * desired_y).sqrt();
+        if d_mag > 0.0 { desired_x = (desired_x / d_mag) * speed; desired_y = (desired_y / d_mag) * speed; }
+        let steer_x = (desired_x - self.vx) * STEERING_RESPONSE; let steer_y = (desired_y - self.vy) * STEERING_RESPONSE;
+        let steer_mag_sq = steer_x * steer_x + steer_y * steer_y;
+        if steer_mag_sq > self.max_force * self.max_force { let mag = steer_mag_sq.sqrt(); return ((steer_x / mag) * self.max_force, (steer_y / mag) * self.max_force); }
+        (steer_x, steer_y)
+    }
+    /// A leaving fish heads for a point well beyond the nearer side wall, ignoring food and the tank margins.
+    fn get_exit_force(&self, width: f64) -> (f64, f64, f64) {
+        let exit_x = if self.x < width * 0.5 { -width } else { width * 2.0 };
+        let (steer_x, steer_y) = self.steer_towards(exit_x, self.y);
+        (steer_x, steer_y, 1.0)
+    }
     fn get_avoid_force(&self, floor_y: f64, width: f64, _height: f64, closest_food: Option<(f64, f64, f64)>) -> (f64, f64, f64) {
         const MARGIN: f64 = 60.0;
         let mut total_steer_x = 0.0; let mut total_steer_y = 0.0; let mut should_avoid = false;
@@ -158,14 +461,155 @@ impl Fish {
         if self.y < MARGIN && !(fy.is_some() && fy.unwrap() < self.y) { total_steer_y += self.max_speed - self.vy; should_avoid = true; }
         if self.y > floor_y - MARGIN && !(fy.is_some() && fy.unwrap() > self.y) { total_steer_y += -self.max_speed - self.vy; should_avoid = true; }
         if should_avoid {
+            total_steer_x *= STEERING_RESPONSE; total_steer_y *= STEERING_RESPONSE;
             let mag_sq = total_steer_x * total_steer_x + total_steer_y * total_steer_y;
             if mag_sq > self.max_force * self.max_force { let mag = mag_sq.sqrt(); return ((total_steer_x / mag) * self.max_force, (total_steer_y / mag) * self.max_force, 1.0); }
             return (total_steer_x, total_steer_y, 1.0);
         }
         (0.0, 0.0, 0.0)
     }
-    fn wander(&mut self) -> (f64, f64) {
-        let mut rng = rand::thread_rng(); self.wander_angle += rng.gen_range(-0.3..0.3);
+    /// Feeler-based obstacle avoidance: three rays ahead of the fish, and for the nearest hit a turn that slides
+    /// along the struck surface and away from it, more urgently the shorter the feeler reached.
+    fn get_obstacle_force(&self, obstacles: &[Obstacle]) -> (f64, f64, f64) {
+        let speed = (self.vx * self.vx + self.vy * self.vy).sqrt();
+        if obstacles.is_empty() || speed == 0.0 { return (0.0, 0.0, 0.0); }
+        let (dir_x, dir_y) = (self.vx / speed, self.vy / speed);
+        let length = self.size * 2.0 + speed * FEELER_LOOKAHEAD;
+        let mut nearest: Option<obstacles::Hit> = None;
+        for (angle, reach) in [(0.0, 1.0), (FEELER_ANGLE, SIDE_FEELER_LENGTH), (-FEELER_ANGLE, SIDE_FEELER_LENGTH)] {
+            let (sin, cos) = f64::sin_cos(angle);
+            let (dx, dy) = ((dir_x * cos - dir_y * sin) * length * reach, (dir_x * sin + dir_y * cos) * length * reach);
+            for hit in obstacles.iter().filter_map(|o| o.cast(self.x, self.y, dx, dy)) {
+                // Side feelers are shorter, so compare hits by distance rather than by `t`.
+                let hit = obstacles::Hit { t: hit.t * reach, ..hit };
+                if nearest.is_none_or(|n| hit.t < n.t) { nearest = Some(hit); }
+            }
+        }
+        let Some(hit) = nearest else { return (0.0, 0.0, 0.0); };
+        let (nx, ny) = hit.normal;
+        let along = dir_x * nx + dir_y * ny;
+        let urgency = 1.0 - hit.t;
+        let (steer_x, steer_y) = self.steer_along(dir_x - along * nx + nx * urgency, dir_y - along * ny + ny * urgency);
+        (steer_x, steer_y, urgency.sqrt())
+    }
+    /// Pushes a fish that ended up inside an obstacle back out to its surface and stops it swimming further in.
+    fn collide(&mut self, obstacles: &[Obstacle]) {
+        let Some(obstacle) = obstacles.iter().find(|o| o.contains(self.x, self.y)) else { return; };
+        let (px, py) = obstacle.nearest_boundary_point(self.x, self.y);
+        let (dx, dy) = (px - self.x, py - self.y);
+        let dist = (dx * dx + dy * dy).sqrt().max(1e-6);
+        let (nx, ny) = (dx / dist, dy / dist);
+        // Only just past the surface: a larger step could land inside again next to a concave corner.
+        self.x = px + nx * 0.01; self.y = py + ny * 0.01;
+        let into = self.vx * nx + self.vy * ny;
+        if into < 0.0 { self.vx -= into * nx; self.vy -= into * ny; }
+    }
+    /// Whether `(x, y)` lies on the fish's body as drawn, give or take `PICK_SLACK` pixels.
+    fn hit(&self, x: f64, y: f64) -> bool {
+        if !self.is_alive() { return false; }
+        let (sin, cos) = self.swim.heading.sin_cos();
+        let (dx, dy) = (x - self.x, y - self.y);
+        // Undo the rotation and the roll/lean squash `draw` applies.
+        let squash = self.swim.facing.abs().max(0.15) * (1.0 - BANK_SQUASH * self.swim.bank.abs());
+        let (local_x, local_y) = (dx * cos + dy * sin, (-dx * sin + dy * cos) / squash);
+        let (center_x, radius_x, radius_y) = self.species.body();
+        let (radius_x, radius_y) = (radius_x * self.size + PICK_SLACK, radius_y * self.size + PICK_SLACK);
+        ((local_x - center_x * self.size) / radius_x).powi(2) + (local_y / radius_y).powi(2) <= 1.0
+    }
+    /// A held fish follows the pointer to `(x, y)` and struggles, beating its tail hard.
+    fn hold_at(&mut self, x: f64, y: f64, dt: f64) {
+        self.prev_x = self.x; self.prev_y = self.y;
+        self.x = x; self.y = y; self.vx = 0.0; self.vy = 0.0; self.startle = None;
+        self.swim.burst = 1.0;
+        self.swim.update(0.0, 0.0, self.size, 0.0, 1.0, dt);
+    }
+    /// Sends the fish bolting away from `(x, y)` for up to `STARTLE_SECONDS`, scaled by `strength`.
+    fn startle(&mut self, x: f64, y: f64, strength: f64) {
+        if !self.is_alive() || self.leaving { return; }
+        let strength = strength.clamp(0.0, 1.0);
+        self.startle = Some(Startle { x, y, left: STARTLE_SECONDS * strength });
+        // A startle is a reflex: the fish darts off at once instead of turning and accelerating.
+        let (dx, dy) = (self.x - x, self.y - y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        let (dir_x, dir_y) = if dist > 0.0 { (dx / dist, dy / dist) } else { (-self.vx.signum(), 0.0) };
+        let speed = self.max_speed + FLEE_SPEED_BOOST * strength;
+        self.vx = dir_x * speed; self.vy = dir_y * speed;
+        self.swim.burst = 1.0;
+    }
+    fn get_startle_force(&mut self, dt: f64) -> (f64, f64, f64) {
+        let Some(startle) = &mut self.startle else { return (0.0, 0.0, 0.0); };
+        startle.left -= dt;
+        let Startle { x, y, left } = *startle;
+        if left <= 0.0 { self.startle = None; return (0.0, 0.0, 0.0); }
+        let urgency = (left / STARTLE_SECONDS).sqrt();
+        let (steer_x, steer_y) = self.steer_at(self.x - x, self.y - y, self.max_speed + FLEE_SPEED_BOOST * urgency);
+        (steer_x, steer_y, urgency)
+    }
+    /// Swims over to an interesting point and hovers a few body lengths from it.
+    fn get_curiosity_force(&self, interest: Option<(f64, f64, f64)>) -> (f64, f64, f64) {
+        let Some((x, y, weight)) = interest else { return (0.0, 0.0, 0.0); };
+        let close = self.size * CURIOUS_DISTANCE;
+        let (steer_x, steer_y) = if (x - self.x).powi(2) + (y - self.y).powi(2) < close * close { self.steer_at(-self.vx, -self.vy, 0.0) } else { self.steer_towards(x, y) };
+        (steer_x, steer_y, weight)
+    }
+    /// Classic boids: keep a body length or two from schoolmates, match their heading and drift towards their center,
+    /// weighted by the species' schooling habit. `neighbours` are fish of the same species within `NEIGHBOR_RADIUS`.
+    fn get_flock_force(&self, neighbours: &[Boid]) -> (f64, f64) {
+        if neighbours.is_empty() { return (0.0, 0.0); }
+        let (mut sep_x, mut sep_y, mut sum_x, mut sum_y, mut sum_vx, mut sum_vy) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        let too_close = self.size * SEPARATION_DISTANCE;
+        for other in neighbours {
+            let (dx, dy) = (self.x - other.x, self.y - other.y);
+            let dist_sq = dx * dx + dy * dy;
+            if dist_sq > 0.0 && dist_sq < too_close * too_close { sep_x += dx / dist_sq; sep_y += dy / dist_sq; }
+            sum_x += other.x; sum_y += other.y; sum_vx += other.vx; sum_vy += other.vy;
+        }
+        let n = neighbours.len() as f64;
+        let weights = self.species.spec().flock;
+        let (mut force_x, mut force_y) = (0.0, 0.0);
+        let mut add = |(x, y): (f64, f64), weight: f64| { force_x += x * weight; force_y += y * weight; };
+        if sep_x != 0.0 || sep_y != 0.0 { add(self.steer_along(sep_x, sep_y), weights.separation); }
+        if sum_vx != 0.0 || sum_vy != 0.0 { add(self.steer_along(sum_vx / n, sum_vy / n), weights.alignment); }
+        add(self.steer_towards(sum_x / n, sum_y / n), weights.cohesion);
+        let mag_sq = force_x * force_x + force_y * force_y;
+        if mag_sq > self.max_force * self.max_force { let mag = mag_sq.sqrt(); return (force_x / mag * self.max_force, force_y / mag * self.max_force); }
+        (force_x, force_y)
+    }
+    fn is_predator(&self) -> bool { self.species.spec().predator }
+    /// Pursuit: head for where the prey will be by the time the predator could get there.
+    fn get_pursuit_force(&self, prey: Boid) -> (f64, f64, f64) {
+        let dist = ((prey.x - self.x).powi(2) + (prey.y - self.y).powi(2)).sqrt();
+        let lookahead = (dist / (self.max_speed + HUNT_SPEED_BOOST)).min(2.0);
+        let (aim_x, aim_y) = (prey.x + prey.vx * lookahead - self.x, prey.y + prey.vy * lookahead - self.y);
+        let (steer_x, steer_y) = self.steer_at(aim_x, aim_y, self.max_speed + HUNT_SPEED_BOOST * self.appetite());
+        (steer_x, steer_y, self.appetite())
+    }
+    /// Evasion: swim away from where each predator in sight is heading, more urgently the closer it is.
+    fn get_evade_force(&self, threats: &[Boid], flee_radius: f64) -> (f64, f64, f64) {
+        let (mut away_x, mut away_y, mut urgency) = (0.0, 0.0, 0.0f64);
+        for threat in threats {
+            let dist = ((threat.x - self.x).powi(2) + (threat.y - self.y).powi(2)).sqrt();
+            let lookahead = (dist / self.max_speed.max(1.0)).min(1.0);
+            let (dx, dy) = (self.x - (threat.x + threat.vx * lookahead), self.y - (threat.y + threat.vy * lookahead));
+            let (d, closeness) = ((dx * dx + dy * dy).sqrt().max(1e-6), (1.0 - dist / flee_radius).clamp(0.0, 1.0));
+            away_x += dx / d * closeness; away_y += dy / d * closeness; urgency = urgency.max(closeness);
+        }
+        if urgency == 0.0 { return (0.0, 0.0, 0.0); }
+        let urgency = urgency.sqrt();
+        let (steer_x, steer_y) = self.steer_at(away_x, away_y, self.max_speed + FLEE_SPEED_BOOST * urgency);
+        (steer_x, steer_y, urgency)
+    }
+    /// A gentle vertical pull back into the species' preferred depth band. Inside the band it damps vertical
+    /// speed instead, since fish mostly cruise level.
+    fn get_depth_force(&self, height: f64) -> (f64, f64) {
+        let (top, bottom) = self.species.spec().depth;
+        let (top, bottom) = (top * height, bottom * height);
+        let off_band = if self.y < top { top - self.y } else if self.y > bottom { bottom - self.y } else { 0.0 };
+        if off_band == 0.0 { return (0.0, -self.vy * LEVEL_DAMPING); }
+        (0.0, (off_band / (height * 0.1)).clamp(-1.0, 1.0) * self.max_force * 0.3)
+    }
+    fn wander(&mut self, dt: f64, rng: &mut StdRng) -> (f64, f64) {
+        self.wander_angle += rng.gen_range(-WANDER_JITTER..WANDER_JITTER) * dt;
         let (circle_dist, circle_radius) = (50.0, 25.0);
         let norm = (self.vx * self.vx + self.vy * self.vy).sqrt();
         let (circle_center_x, circle_center_y) = if norm > 0.0 { (self.vx / norm * circle_dist, self.vy / norm * circle_dist) } else { (circle_dist, 0.0) };
@@ -175,70 +619,511 @@ impl Fish {
         if mag > 0.0 { return ((wander_force_x / mag) * self.max_force * 0.2, (wander_force_y / mag) * self.max_force * 0.2); }
         (0.0, 0.0)
     }
-    fn update(&mut self, food_items: &[Food], floor_y: f64, width: f64, height: f64) {
-        let closest_food = food_items.iter().map(|f| ((self.x - f.x).powi(2) + (self.y - f.y).powi(2), f.x, f.y)).min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        let (seek_x, seek_y, seek_w) = self.get_seek_force(closest_food, width, height);
-        let (avoid_x, avoid_y, avoid_w) = self.get_avoid_force(floor_y, width, height, closest_food);
-        let (wander_x, wander_y) = self.wander();
-        let force_x = avoid_x * avoid_w + seek_x * seek_w * (1.0 - avoid_w) + wander_x * (1.0 - seek_w) * (1.0 - avoid_w);
-        let force_y = avoid_y * avoid_w + seek_y * seek_w * (1.0 - avoid_w) + wander_y * (1.0 - seek_w) * (1.0 - avoid_w);
-        self.apply_force(force_x, force_y);
-        self.vx += self.ax; self.vy += self.ay;
-        let current_max_speed = self.max_speed + (seek_w * 0.5);
+    /// Blends the steering behaviours by priority: wall and obstacle avoidance override fleeing (from predators or
+    /// a startle), fleeing overrides feeding or hunting, and those override curiosity, schooling and wandering, so a
+    /// school scatters towards food and regroups once it is gone. `pace` scales the fish's top speed and how hard it
+    /// can steer, below `1` in calm mode.
+    #[allow(clippy::too_many_arguments)]
+    fn update(&mut self, senses: &Senses, config: &AquariumConfig, floor_y: f64, width: f64, height: f64, pace: f64, dt: f64, rng: &mut StdRng) {
+        self.prev_x = self.x; self.prev_y = self.y;
+        if !self.is_alive() {
+            // Dead fish stop swimming and slowly float up.
+            self.vx *= 0.5f64.powf(dt); self.vy = -DEATH_FLOAT_SPEED;
+            self.x += self.vx * dt; self.y += self.vy * dt;
+            return;
+        }
+        // A hunting predator treats its prey like food: it may chase it right up to the walls.
+        let target = match senses.prey { Some(prey) => Some((0.0, prey.x, prey.y)), None => senses.closest_food };
+        let (seek_x, seek_y, seek_w) = if self.leaving { self.get_exit_force(width) }
+            else if let Some(prey) = senses.prey { self.get_pursuit_force(prey) }
+            else { self.get_seek_force(senses.closest_food, width, height) };
+        let (wall_x, wall_y, wall_w) = if self.leaving { (0.0, 0.0, 0.0) } else { self.get_avoid_force(floor_y, width, height, target) };
+        let (obstacle_x, obstacle_y, obstacle_w) = self.get_obstacle_force(senses.obstacles);
+        let avoid_w = wall_w.max(obstacle_w);
+        let (avoid_x, avoid_y) = if avoid_w > 0.0 { ((wall_x * wall_w + obstacle_x * obstacle_w) / avoid_w, (wall_y * wall_w + obstacle_y * obstacle_w) / avoid_w) } else { (0.0, 0.0) };
+        let (flee_x, flee_y, flee_w) = if self.leaving { (0.0, 0.0, 0.0) } else { self.get_evade_force(senses.threats, config.predation.flee_radius) };
+        let (startle_x, startle_y, startle_w) = self.get_startle_force(dt);
+        let (flee_x, flee_y, flee_w) = if startle_w > flee_w { (startle_x, startle_y, startle_w) } else { (flee_x, flee_y, flee_w) };
+        let (wander_x, wander_y) = self.wander(dt, rng);
+        let (flock_x, flock_y) = if self.leaving { (0.0, 0.0) } else { self.get_flock_force(senses.neighbours) };
+        let (_, depth_y) = self.get_depth_force(height);
+        let (cruise_x, cruise_y) = (wander_x + flock_x * config.schooling, wander_y + flock_y * config.schooling + depth_y);
+        let (curious_x, curious_y, curious_w) = if self.leaving { (0.0, 0.0, 0.0) } else { self.get_curiosity_force(senses.interest) };
+        let (cruise_x, cruise_y) = (cruise_x * (1.0 - curious_w) + curious_x * curious_w, cruise_y * (1.0 - curious_w) + curious_y * curious_w);
+        let (goal_x, goal_y) = (seek_x * seek_w + cruise_x * (1.0 - seek_w), seek_y * seek_w + cruise_y * (1.0 - seek_w));
+        let force_x = avoid_x * avoid_w + (flee_x * flee_w + goal_x * (1.0 - flee_w)) * (1.0 - avoid_w);
+        let force_y = avoid_y * avoid_w + (flee_y * flee_w + goal_y * (1.0 - flee_w)) * (1.0 - avoid_w);
+        self.apply_force(force_x * pace, force_y * pace);
+        self.vx += self.ax * dt; self.vy += self.ay * dt;
+        let seek_boost = if senses.prey.is_some() { HUNT_SPEED_BOOST } else { FEEDING_SPEED_BOOST };
+        let current_max_speed = (self.max_speed + (seek_w * seek_boost).max(flee_w * FLEE_SPEED_BOOST)) * pace;
         let speed_sq = self.vx.powi(2) + self.vy.powi(2);
-        if speed_sq > current_max_speed.powi(2) { let speed = speed_sq.sqrt(); self.vx = (self.vx / speed) * current_max_speed; self.vy = (self.vy / speed) * current_max_speed; }
-        self.x += self.vx; self.y += self.vy;
+        if speed_sq > current_max_speed.powi(2) {
+            // Above its own top speed (say, thrown by the pointer) a fish coasts down to it rather than stopping dead.
+            let speed = speed_sq.sqrt();
+            let limit = current_max_speed + (speed - current_max_speed) * (-COAST_DRAG * dt).exp();
+            self.vx = (self.vx / speed) * limit; self.vy = (self.vy / speed) * limit;
+        }
+        self.x += self.vx * dt; self.y += self.vy * dt;
+        self.collide(senses.obstacles);
+        self.swim.update(self.vx, self.vy, self.size, seek_w, pace, dt);
         self.ax = 0.0; self.ay = 0.0;
     }
-    fn draw(&self, ctx: &CanvasRenderingContext2d) {
-        ctx.save(); ctx.translate(self.x, self.y).unwrap(); ctx.rotate(self.vy.atan2(self.vx)).unwrap();
-        ctx.set_fill_style(&JsValue::from_str(&self.color));
-        ctx.begin_path(); ctx.move_to(-self.size * 0.9, 0.0); ctx.line_to(-self.size * 1.5, -self.size * 0.6); ctx.line_to(-self.size * 1.4, 0.0); ctx.line_to(-self.size * 1.5, self.size * 0.6); ctx.close_path(); ctx.fill();
-        ctx.begin_path(); ctx.ellipse(0.0, 0.0, self.size, self.size * 0.6, 0.0, 0.0, std::f64::consts::PI * 2.0).unwrap(); ctx.fill();
-        ctx.set_fill_style(&JsValue::from_str("white")); ctx.begin_path(); ctx.arc(self.size * 0.6, 0.0, self.size * 0.15, 0.0, std::f64::consts::PI * 2.0).unwrap(); ctx.fill();
-        ctx.set_fill_style(&JsValue::from_str("black")); ctx.begin_path(); ctx.arc(self.size * 0.65, 0.0, self.size * 0.08, 0.0, std::f64::consts::PI * 2.0).unwrap(); ctx.fill();
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f64) {
+        ctx.save(); ctx.translate(lerp(self.prev_x, self.x, alpha), lerp(self.prev_y, self.y, alpha));
+        match self.dead_for {
+            // Belly-up, facing the way it last swam.
+            Some(dead_for) => { ctx.set_global_alpha((1.0 - dead_for / DEATH_FADE).max(0.0)); ctx.scale(if self.vx < 0.0 { -1.0 } else { 1.0 }, -1.0); }
+            None => {
+                // Mirrored rather than upside down when facing left, squashed while rolling over and leaning into turns.
+                let facing = self.swim.facing.signum() * self.swim.facing.abs().max(0.15);
+                ctx.rotate(self.swim.heading); ctx.scale(1.0, facing * (1.0 - BANK_SQUASH * self.swim.bank.abs()));
+            }
+        }
+        let pose = if self.is_alive() { self.swim.pose((self.vx * self.vx + self.vy * self.vy).sqrt() / self.max_speed.max(1.0)) } else { Pose::REST };
+        self.species.draw(ctx, self.size, &self.color, &self.accent, pose);
         ctx.restore();
     }
 }
 
+// --- Pointer System ---
+/// Where a pointer is over the tank and its smoothed velocity, measured over the frames it moved in. `moved` collects
+/// the movement since the last frame.
+struct PointerState { id: i32, x: f64, y: f64, moved: (f64, f64), vx: f64, vy: f64 }
+impl PointerState {
+    fn speed(&self) -> f64 { (self.vx * self.vx + self.vy * self.vy).sqrt() }
+}
+
+/// A fish held by pointer `id`, and where on the fish it was picked up.
+struct Grab { id: i32, offset_x: f64, offset_y: f64 }
+
+/// A temperament's reaction to the pointer: the speed in pixels per second below which fish come to look, the speed
+/// above which they scatter, and how far a tap on the glass carries relative to the pointer radius.
+struct PointerReaction { curious_below: f64, startle_above: f64, tap_reach: f64 }
+fn pointer_reaction(temperament: Temperament) -> PointerReaction {
+    match temperament {
+        Temperament::Friendly => PointerReaction { curious_below: 250.0, startle_above: 2500.0, tap_reach: 0.75 },
+        Temperament::Skittish => PointerReaction { curious_below: 0.0, startle_above: 600.0, tap_reach: 1.5 },
+        Temperament::Indifferent => PointerReaction { curious_below: 0.0, startle_above: f64::INFINITY, tap_reach: 1.0 },
+    }
+}
+
 // --- Main Aquarium System ---
-#[wasm_bindgen]
+/// The simulated tank: every entity, the clock and the random source. It knows nothing about the browser; the
+/// `web` module binds it to a canvas and an animation loop, and `draw` can paint it onto any `Renderer`.
 pub struct Aquarium {
-    ctx: CanvasRenderingContext2d, width: f64, height: f64,
+    width: f64, height: f64,
     god_rays: Vec<GodRay>, fishes: Vec<Fish>, food: Vec<Food>, crabs: Vec<Crab>,
-    bubbles: Vec<Bubble>, frame_count: f64, castle_base_y: f64,
-    castle_seed: u64,
+    bubbles: Vec<Bubble>, elapsed: f64, accumulator: f64, castle_base_y: f64,
+    castle_seed: u64, rng: StdRng, config: AquariumConfig,
+    /// Indexes of fish and food positions, rebuilt every step for the neighbour and closest-food lookups.
+    fish_grid: SpatialGrid, food_grid: SpatialGrid,
+    /// Static scene geometry fish swim around, in tank coordinates; rebuilt when the tank is resized.
+    obstacles: Vec<Obstacle>,
+    /// Pointers over the tank, and presses on their way to becoming gestures.
+    pointers: Vec<PointerState>, gestures: Gestures,
+    grab: Option<Grab>,
+    /// Where the keyboard drops food, shown while the tank has keyboard focus.
+    reticle: (f64, f64), reticle_visible: bool,
+    /// The latest event worth telling a screen reader about, until it is taken.
+    announcement: Option<String>,
+    prefers_reduced_motion: bool,
+    events: EventLog,
+    /// The last id given to a fish or crab.
+    next_id: u32,
+}
+impl Aquarium {
+    pub fn get_castle_scale(&self) -> f64 { castle_scale_for(self.height) }
+    pub fn add_food(&mut self, x: f64, y: f64) { self.food.push(Food { x, y, prev_y: y, vy: 0.0 }); self.events.push(TankEvent::FoodDropped { x, y }); }
+    /// Adds a fish of a random configured species at `(x, y)`.
+    pub fn add_fish(&mut self, x: f64, y: f64) { let mut fish = Fish::random(&self.config, self.width, self.height, &mut self.rng); fish.place(x, y); self.spawn_fish(fish, SpawnCause::Added); }
+    /// Adds a fish of the given species at `(x, y)`, whether or not the config lists it.
+    pub fn add_fish_of(&mut self, species: Species, x: f64, y: f64) {
+        let mut fish = Fish::new(species, self.width, self.height, &self.config, &mut self.rng); fish.place(x, y); self.spawn_fish(fish, SpawnCause::Added);
+    }
+    fn spawn_fish(&mut self, mut fish: Fish, cause: SpawnCause) {
+        fish.id = self.next_id();
+        self.events.push(TankEvent::FishSpawned { fish: fish.id, species: fish.species, x: fish.x, y: fish.y, cause });
+        self.fishes.push(fish);
+    }
+    fn next_id(&mut self) -> u32 { self.next_id += 1; self.next_id }
+
+    // --- Event System ---
+    /// Starts or stops collecting events for `take_events`. Nothing is collected until someone listens.
+    pub fn listen_for_events(&mut self, listening: bool) { self.events.listen(listening); }
+    /// The events since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<TankEvent> { self.events.take() }
+    /// The height of the sand at `x`. It rolls with time, except when motion is reduced.
+    pub fn get_seafloor_y_at(&self, x: f64) -> f64 {
+        let t = if self.motion() == Motion::Full { self.elapsed } else { 0.0 };
+        let base_height = self.height * 0.9; let wave1 = (x * 0.005 + t * 0.6).sin() * 10.0; let wave2 = (x * 0.02 + t * 0.3).sin() * 5.0; base_height + wave1 + wave2
+    }
+
+    // --- Motion System ---
+    /// How much the tank actually moves: the configured `motion`, with `auto` resolved from the visitor's preference.
+    pub fn motion(&self) -> Motion {
+        match self.config.motion { Motion::Auto if self.prefers_reduced_motion => Motion::Calm, Motion::Auto => Motion::Full, motion => motion }
+    }
+    /// Reports the visitor's `prefers-reduced-motion` setting, which `motion: auto` follows.
+    pub fn set_prefers_reduced_motion(&mut self, reduced: bool) { self.prefers_reduced_motion = reduced; }
+
+    /// Performs the configured interaction for a mouse `button` (as in `MouseEvent.button`) pressed at `(x, y)`.
+    pub fn handle_click(&mut self, x: f64, y: f64, button: i16) {
+        let action = match button { 0 => self.config.interaction.click, 2 => self.config.interaction.right_click, _ => Action::None };
+        self.perform(action, x, y, 0.5);
+    }
+    /// Carries out a gesture's action. A firm press of a pen or finger sprinkles extra food.
+    fn perform(&mut self, action: Action, x: f64, y: f64, pressure: f64) {
+        match action {
+            Action::Food => {
+                let extra = ((pressure - 0.5).max(0.0) * PELLETS_PER_PRESSURE) as usize;
+                self.add_food(x, y);
+                for _ in 0..extra { let (dx, dy) = (self.rng.gen_range(-8.0..8.0), self.rng.gen_range(-8.0..8.0)); self.add_food(x + dx, y + dy); }
+            }
+            Action::Fish => self.add_fish(x, y), Action::Tap => self.tap_glass(x, y), Action::None => {}
+        }
+    }
+    /// A pointer pressed on the tank. With `interaction.dragFish`, a primary press on a fish picks up the topmost one
+    /// there, which then follows this pointer until it is released; returns whether a fish was picked up. Other
+    /// presses become clicks, taps or long presses, and trigger their configured action when recognised.
+    pub fn pointer_down(&mut self, input: PointerInput) -> bool {
+        self.pointer_move(input);
+        let grabbed = input.button == 0 && self.config.interaction.drag_fish && self.grab.is_none() && self.grab_fish(input);
+        self.gestures.press(input, grabbed);
+        grabbed
+    }
+    /// Tracks a pointer over the tank, pressed or not. Depending on `interaction.temperament`, fish gather near a slow
+    /// pointer and scatter from a fast one.
+    pub fn pointer_move(&mut self, input: PointerInput) {
+        self.gestures.drag(input);
+        match self.pointers.iter_mut().find(|p| p.id == input.id) {
+            Some(pointer) => { pointer.moved.0 += input.x - pointer.x; pointer.moved.1 += input.y - pointer.y; pointer.x = input.x; pointer.y = input.y; }
+            None => self.pointers.push(PointerState { id: input.id, x: input.x, y: input.y, moved: (0.0, 0.0), vx: 0.0, vy: 0.0 }),
+        }
+    }
+    /// A pointer released: lets go of the fish it holds, throwing it with the pointer's velocity, or completes a
+    /// click or tap.
+    pub fn pointer_up(&mut self, input: PointerInput) {
+        self.pointer_move(input);
+        self.release_fish(input.id);
+        if let Some(recognized) = self.gestures.release(input) { self.recognized(recognized); }
+    }
+    /// A press the browser took over (for scrolling, say): the fish is dropped and no gesture recognised.
+    pub fn pointer_cancel(&mut self, id: i32) { self.release_fish(id); self.gestures.cancel(id); }
+    /// Forgets a pointer once it leaves the tank, dropping any fish it holds.
+    pub fn pointer_leave(&mut self, id: i32) { self.pointer_cancel(id); self.pointers.retain(|p| p.id != id); }
+    fn recognized(&mut self, recognized: Recognized) {
+        let interaction = &self.config.interaction;
+        let action = match recognized.gesture { Gesture::Tap => interaction.click, Gesture::SecondaryTap => interaction.right_click, Gesture::LongPress => interaction.long_press };
+        self.perform(action, recognized.x, recognized.y, recognized.pressure);
+    }
+    fn grab_fish(&mut self, input: PointerInput) -> bool {
+        // Later fish are drawn over earlier ones, so search from the top down.
+        let Some(i) = self.fishes.iter().rposition(|f| f.hit(input.x, input.y)) else { return false; };
+        let mut fish = self.fishes.remove(i);
+        fish.held = true;
+        self.grab = Some(Grab { id: input.id, offset_x: fish.x - input.x, offset_y: fish.y - input.y });
+        // While held, the fish is drawn on top of the others.
+        self.fishes.push(fish);
+        true
+    }
+    fn release_fish(&mut self, id: i32) {
+        if self.grab.as_ref().is_none_or(|g| g.id != id) { return; }
+        self.grab = None;
+        let (vx, vy) = self.pointers.iter().find(|p| p.id == id).map_or((0.0, 0.0), |p| (p.vx, p.vy));
+        let speed = (vx * vx + vy * vy).sqrt();
+        let scale = if speed > MAX_THROW_SPEED { MAX_THROW_SPEED / speed } else { 1.0 };
+        if let Some(fish) = self.fishes.iter_mut().find(|f| f.held) { fish.held = false; fish.vx = vx * scale; fish.vy = vy * scale; }
+    }
+    /// Whether a fish is being dragged.
+    pub fn is_holding_fish(&self) -> bool { self.grab.is_some() }
+
+    // --- Query System ---
+    pub fn counts(&self) -> Counts { Counts { fish: self.fishes.len(), food: self.food.len(), crabs: self.crabs.len(), bubbles: self.bubbles.len() } }
+    /// Every fish, in drawing order (the last is on top).
+    pub fn fish_snapshots(&self) -> Vec<FishSnapshot> { self.fishes.iter().map(Fish::snapshot).collect() }
+    /// The fish with the given id, while it is in the tank.
+    pub fn fish_snapshot(&self, id: u32) -> Option<FishSnapshot> { self.fishes.iter().find(|f| f.id == id).map(Fish::snapshot) }
+    pub fn crab_snapshots(&self) -> Vec<CrabSnapshot> { self.crabs.iter().map(Crab::snapshot).collect() }
+    pub fn food_snapshots(&self) -> Vec<FoodSnapshot> { self.food.iter().map(|f| FoodSnapshot { x: f.x, y: f.y, vy: f.vy }).collect() }
+    pub fn bubble_snapshots(&self) -> Vec<BubbleSnapshot> { self.bubbles.iter().map(Bubble::snapshot).collect() }
+    /// The topmost thing drawn at `(x, y)`, if any, with the same generous margin used for picking up fish. Only the
+    /// layers being drawn are searched.
+    pub fn entity_at(&self, x: f64, y: f64) -> Option<EntitySnapshot> {
+        let layers = &self.config.layers;
+        let near = |px: f64, py: f64, radius: f64| (px - x).powi(2) + (py - y).powi(2) < (radius + PICK_SLACK).powi(2);
+        // Searched in reverse drawing order, so what is on top is found first.
+        if layers.food && let Some(f) = self.food.iter().rev().find(|f| near(f.x, f.y, 3.0)) { return Some(EntitySnapshot::Food(FoodSnapshot { x: f.x, y: f.y, vy: f.vy })); }
+        if layers.fish && let Some(fish) = self.fishes.iter().rev().find(|f| f.hit(x, y)) { return Some(EntitySnapshot::Fish(fish.snapshot())); }
+        if layers.crabs && let Some(crab) = self.crabs.iter().rev().find(|c| c.hit(x, y)) { return Some(EntitySnapshot::Crab(crab.snapshot())); }
+        if layers.bubbles && let Some(b) = self.bubbles.iter().rev().find(|b| near(b.x, b.y, b.size)) { return Some(EntitySnapshot::Bubble(b.snapshot())); }
+        None
+    }
+
+    // --- Keyboard System ---
+    /// Handles a key by its `KeyboardEvent.key` (see `interaction.keyboard` for the bindings) and returns the command
+    /// it was bound to. Pausing is left to whoever drives the animation.
+    pub fn key_down(&mut self, key: &str) -> Option<KeyCommand> {
+        if !self.config.interaction.keyboard { return None; }
+        let command = KeyCommand::parse(key)?;
+        match command {
+            KeyCommand::Feed => {
+                let (x, y) = (self.width * self.rng.gen_range(0.1..0.9), self.height * self.rng.gen_range(0.05..0.3));
+                self.add_food(x, y);
+                self.announce("Food dropped".to_string());
+            }
+            KeyCommand::AddFish => {
+                let (x, y) = (self.width * self.rng.gen_range(0.1..0.9), self.height * self.rng.gen_range(0.2..0.7));
+                self.add_fish(x, y);
+                let count = self.fishes.iter().filter(|f| !f.leaving).count();
+                self.announce(format!("Fish added, {} in the tank", count));
+            }
+            KeyCommand::Move(dx, dy) => {
+                self.reticle_visible = true;
+                let (x, y) = (self.reticle.0 + dx * RETICLE_STEP, self.reticle.1 + dy * RETICLE_STEP);
+                self.reticle = (x.clamp(0.0, self.width), y.clamp(0.0, self.height * 0.9));
+                let (across, down) = ((self.reticle.0 / self.width * 100.0).round(), (self.reticle.1 / self.height * 100.0).round());
+                self.announce(format!("Feeding spot {}% across, {}% down", across, down));
+            }
+            KeyCommand::Drop => { self.reticle_visible = true; self.add_food(self.reticle.0, self.reticle.1); self.announce("Food dropped at the feeding spot".to_string()); }
+            KeyCommand::Tap => { self.reticle_visible = true; self.tap_glass(self.reticle.0, self.reticle.1); self.announce("Tapped on the glass".to_string()); }
+            KeyCommand::Pause => {}
+        }
+        Some(command)
+    }
+    /// Whether keys do anything in the tank (`interaction.keyboard`), in which case the canvas should take focus.
+    pub fn accepts_keyboard(&self) -> bool { self.config.interaction.keyboard }
+    /// Shows the feeding spot while the tank has keyboard focus, and hides it otherwise.
+    pub fn set_keyboard_focus(&mut self, focused: bool) { self.reticle_visible = focused && self.config.interaction.keyboard; }
+    pub(crate) fn announce(&mut self, message: String) { self.announcement = Some(message); }
+    /// Takes the latest announcement for a screen reader's live region, if there is one since the last call.
+    pub fn take_announcement(&mut self) -> Option<String> { self.announcement.take() }
+    /// A knock on the glass at `(x, y)`: fish nearby bolt away from it, the closest ones the longest.
+    pub fn tap_glass(&mut self, x: f64, y: f64) {
+        let interaction = &self.config.interaction;
+        self.startle_fishes(x, y, interaction.pointer_radius * pointer_reaction(interaction.temperament).tap_reach);
+    }
+    fn startle_fishes(&mut self, x: f64, y: f64, radius: f64) {
+        for fish in &mut self.fishes {
+            let dist = ((fish.x - x).powi(2) + (fish.y - y).powi(2)).sqrt();
+            if dist < radius && !fish.held { fish.startle(x, y, 1.5 - dist / radius); }
+        }
+    }
+    /// Whether pressing on a fish picks it up (`interaction.dragFish`), in which case touches on the canvas must not
+    /// scroll the page.
+    pub fn drags_fish(&self) -> bool { self.config.interaction.drag_fish }
+    /// Whether a right click or long press does something in the tank, in which case the browser's context menu should
+    /// be suppressed.
+    pub fn captures_context_menu(&self) -> bool { self.config.interaction.right_click != Action::None || self.config.interaction.long_press != Action::None }
+
+    /// Sets the fish population target. Surplus fish swim out of the tank and new ones swim in from the sides,
+    /// rather than popping in or out of existence.
+    pub fn set_fish_count(&mut self, count: usize) -> Result<(), String> {
+        AquariumConfig::check_population("fishCount", count)?;
+        self.config.fish_count = count;
+        // Dead fish fading out are on their way out already and count for nothing.
+        let mut staying = self.fishes.iter().filter(|f| f.is_alive() && !f.leaving).count();
+        for fish in self.fishes.iter_mut().rev().filter(|f| f.is_alive()) {
+            if staying > count && !fish.leaving { fish.leaving = true; staying -= 1; }
+            else if staying < count && fish.leaving { fish.leaving = false; staying += 1; }
+        }
+        while staying < count {
+            let mut fish = Fish::random(&self.config, self.width, self.height, &mut self.rng);
+            let from_left = self.rng.gen_bool(0.5);
+            fish.place(if from_left { -fish.size * 1.5 } else { self.width + fish.size * 1.5 }, fish.y);
+            fish.vx = if from_left { fish.max_speed } else { -fish.max_speed };
+            self.spawn_fish(fish, SpawnCause::SwamIn); staying += 1;
+        }
+        Ok(())
+    }
+    /// Sets the crab population target. Surplus crabs walk off the nearer edge; new ones walk in from the sides.
+    pub fn set_crab_count(&mut self, count: usize) -> Result<(), String> {
+        AquariumConfig::check_population("crabCount", count)?;
+        self.config.crab_count = count;
+        let mut staying = self.crabs.iter().filter(|c| !c.leaving).count();
+        for crab in self.crabs.iter_mut().rev() {
+            if staying > count && !crab.leaving { crab.leaving = true; staying -= 1; }
+            else if staying < count && crab.leaving { crab.leaving = false; staying += 1; }
+        }
+        while staying < count {
+            let mut crab = Crab::new(self.width, self.height, &mut self.rng);
+            crab.direction = if self.rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+            crab.x = if crab.direction > 0.0 { -crab.size * 1.5 } else { self.width + crab.size * 1.5 };
+            crab.prev_x = crab.x; crab.id = self.next_id();
+            self.crabs.push(crab); staying += 1;
+        }
+        Ok(())
+    }
+    /// Sets the bubble population target. Extra bubbles start rising right away; surplus ones finish their
+    /// ascent and are not replaced.
+    pub fn set_bubble_count(&mut self, count: usize) -> Result<(), String> {
+        AquariumConfig::check_population("bubbleCount", count)?;
+        self.config.bubble_count = count;
+        let spawn = self.bubble_spawn();
+        while self.bubbles.len() < count { self.bubbles.push(Bubble::new(spawn, &mut self.rng)); }
+        Ok(())
+    }
+    /// Shows or hides one layer of the scene: `godRays`, `castle`, `seafloor`, `bubbles`, `crabs`, `fish` or `food`.
+    pub fn set_layer_visible(&mut self, layer: &str, visible: bool) -> Result<(), String> {
+        if layer == "castle" {
+            // A hidden castle is gone for the fish too, and a shown one is in their way again.
+            self.config.castle.enabled = visible; self.obstacles = self.scene_obstacles();
+            return Ok(());
+        }
+        let flag = self.config.layers.flag_mut(layer).ok_or_else(|| format!("Unknown aquarium layer: {:?}", layer))?;
+        *flag = visible;
+        Ok(())
+    }
+    /// Replaces the color palette. Existing fish are recolored only if the new palette lists fish colors.
+    pub fn set_palette(&mut self, palette: Palette) -> Result<(), String> {
+        palette.validate()?;
+        if !palette.fish.is_empty() { for fish in &mut self.fishes { fish.color = palette.fish.choose(&mut self.rng).unwrap().clone(); } }
+        self.config.palette = palette;
+        Ok(())
+    }
+
+    /// Adapts the tank to a new size in CSS pixels: moves the castle, rescales every entity into the new bounds
+    /// and adds or retires god rays to match the new width.
+    pub fn resize(&mut self, width: f64, height: f64) {
+        if width < 1.0 || height < 1.0 { return; }
+        if width == self.width && height == self.height { return; }
+        let (sx, sy) = (width / self.width, height / self.height);
+        let (old_center_x, old_base_y, old_scale) = (self.castle_center_x(), self.castle_base_y, self.get_castle_scale());
+        self.width = width; self.height = height;
+        self.castle_base_y = height * 0.95;
+        self.obstacles = self.scene_obstacles();
+        let (center_x, scale_ratio) = (self.castle_center_x(), self.get_castle_scale() / old_scale);
+        let castle_enabled = self.config.castle.enabled;
+        for fish in &mut self.fishes {
+            let x = (fish.x * sx).clamp(0.0, width); let y = (fish.y * sy).clamp(0.0, height * 0.9);
+            fish.place(x, y);
+        }
+        self.reticle = (self.reticle.0 * sx, self.reticle.1 * sy);
+        for food_item in &mut self.food { food_item.x *= sx; food_item.y *= sy; food_item.prev_y = food_item.y; }
+        for crab in &mut self.crabs { crab.x *= sx; crab.y *= sy; crab.prev_x = crab.x; crab.prev_y = crab.y; }
+        for bubble in &mut self.bubbles {
+            bubble.original_x = if castle_enabled { center_x + (bubble.original_x - old_center_x) * scale_ratio } else { bubble.original_x * sx };
+            bubble.y = self.castle_base_y + (bubble.y - old_base_y) * sy;
+            bubble.x = bubble.original_x + bubble.wobble_angle.sin() * bubble.size * 0.5;
+            bubble.prev_x = bubble.x; bubble.prev_y = bubble.y;
+        }
+        for ray in &mut self.god_rays { ray.x *= sx; ray.length *= sy; }
+        let ray_count = god_ray_count(width, self.config.god_ray_density);
+        self.god_rays.truncate(ray_count);
+        while self.god_rays.len() < ray_count { self.god_rays.push(GodRay::new(width, height, &mut self.rng)); }
+    }
 }
-#[wasm_bindgen]
 impl Aquarium {
-    #[wasm_bindgen(constructor)]
-    pub fn new(canvas_id: &str) -> Result<Aquarium, JsValue> {
-        // Set up panic hook for better error messages in the console.
-        console_error_panic_hook::set_once();
-        let document = web_sys::window().unwrap().document().unwrap();
-        let canvas = document.get_element_by_id(canvas_id).ok_or_else(|| JsValue::from_str("Canvas element not found"))?.dyn_into::<HtmlCanvasElement>()?;
-        canvas.set_width(web_sys::window().unwrap().inner_width().unwrap().as_f64().unwrap() as u32);
-        canvas.set_height(web_sys::window().unwrap().inner_height().unwrap().as_f64().unwrap() as u32);
-        let ctx = canvas.get_context("2d")?.ok_or_else(|| JsValue::from_str("Could not get 2d context"))?.dyn_into::<CanvasRenderingContext2d>()?;
-        let width = canvas.width() as f64; let height = canvas.height() as f64;
-        let god_rays = (0..(width / 200.0).clamp(3.0, 20.0).round() as usize).map(|_| GodRay::new(width, height)).collect();
-        let fishes = (0..15).map(|_| Fish::new(width, height)).collect();
-        let crabs = (0..3).map(|_| Crab::new(width, height)).collect();
-        let castle_base_y = height * 0.95;
-        let castle_center_x = width * 0.5;
-        let castle_scale = (height / 1000.0).max(0.5) * 1.5;
-        let bubbles = (0..30).map(|_| Bubble::new(castle_center_x, castle_base_y, castle_scale)).collect();
-        let castle_seed: u64 = rand::thread_rng().next_u64();
-        Ok(Self { ctx, width, height, god_rays, fishes, food: Vec::new(), crabs, bubbles, frame_count: 0.0, castle_base_y, castle_seed })
-    }
-    
-    pub fn get_castle_scale(&self) -> f64 { (self.height / 1000.0).max(0.5) * 1.5 }
-    pub fn add_food(&mut self, x: f64, y: f64) { self.food.push(Food { x, y, vy: 0.0 }); }
-    pub fn add_fish(&mut self, x: f64, y: f64) { let mut fish = Fish::new(self.width, self.height); fish.x = x; fish.y = y; self.fishes.push(fish); }
-    pub fn get_seafloor_y_at(&self, x: f64) -> f64 { let base_height = self.height * 0.9; let wave1 = (x * 0.005 + self.frame_count * 0.01).sin() * 10.0; let wave2 = (x * 0.02 + self.frame_count * 0.005).sin() * 5.0; base_height + wave1 + wave2 }
-
-	fn draw_cobblestone(&self, ctx: &CanvasRenderingContext2d, path_def: impl Fn(&CanvasRenderingContext2d)) {
-        let colors = ["#6c757d", "#60686f", "#788088"];
+    /// Creates an aquarium with the default configuration that is not bound to any canvas. Advance it with
+    /// `advance` or `update` and paint it with `draw`. All randomness, including the castle's cobblestone layout,
+    /// is derived from `seed`. Panics if `width` or `height` is not positive.
+    pub fn with_size(width: f64, height: f64, seed: u64) -> Self {
+        Self::with_config(width, height, seed, AquariumConfig::default()).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `with_size`, with explicit settings. `config.seed` is ignored in favour of `seed`. An invalid config or a
+    /// tank without area is an error.
+    pub fn with_config(width: f64, height: f64, seed: u64, config: AquariumConfig) -> Result<Self, String> {
+        if !(width > 0.0 && height > 0.0) { return Err(format!("the tank must have a positive width and height, got {}x{}", width, height)); }
+        config.validate()?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let castle_seed = rng.next_u64();
+        let god_rays = (0..god_ray_count(width, config.god_ray_density)).map(|_| GodRay::new(width, height, &mut rng)).collect();
+        let mut fishes: Vec<Fish> = (0..config.fish_count).map(|_| Fish::random(&config, width, height, &mut rng)).collect();
+        let mut crabs: Vec<Crab> = (0..config.crab_count).map(|_| Crab::new(width, height, &mut rng)).collect();
+        for (i, fish) in fishes.iter_mut().enumerate() { fish.id = i as u32 + 1; }
+        for (i, crab) in crabs.iter_mut().enumerate() { crab.id = (fishes.len() + i) as u32 + 1; }
+        let next_id = (fishes.len() + crabs.len()) as u32;
+        let mut aquarium = Self {
+            width, height, god_rays, fishes, food: Vec::new(), crabs, bubbles: Vec::new(), elapsed: 0.0, accumulator: 0.0,
+            castle_base_y: height * 0.95, castle_seed, rng, config,
+            fish_grid: SpatialGrid::new(NEIGHBOR_RADIUS), food_grid: SpatialGrid::new(FOOD_GRID_CELL), obstacles: Vec::new(), pointers: Vec::new(), gestures: Gestures::default(), grab: None,
+            reticle: (width / 2.0, height / 2.0), reticle_visible: false, announcement: None, prefers_reduced_motion: false,
+            events: EventLog::default(), next_id,
+        };
+        aquarium.obstacles = aquarium.scene_obstacles();
+        let spawn = aquarium.bubble_spawn();
+        aquarium.bubbles = (0..aquarium.config.bubble_count).map(|_| Bubble::new(spawn, &mut aquarium.rng)).collect();
+        Ok(aquarium)
+    }
+
+    /// Feeds `dt` seconds of wall-clock time into the fixed-timestep accumulator and runs the steps that fit.
+    /// The leftover fraction of a step is used by `draw` to interpolate between the last two steps.
+    pub fn advance(&mut self, dt: f64) {
+        // A still tank holds its frame; only interactions change it, so only the input is kept running.
+        let dt = dt.max(0.0);
+        if self.motion() == Motion::Still { self.advance_input(dt); return; }
+        self.track_pointers(dt);
+        self.accumulator += dt;
+        let mut steps = 0;
+        while self.accumulator >= FIXED_STEP {
+            if steps == MAX_STEPS_PER_TICK { self.accumulator %= FIXED_STEP; break; }
+            self.update();
+            self.accumulator -= FIXED_STEP; steps += 1;
+        }
+    }
+
+    /// Advances only what the pointers are doing by `dt` seconds: presses age towards long presses and a held fish
+    /// follows its pointer. For a tank that is not animating (still or paused) while someone interacts with it.
+    pub fn advance_input(&mut self, dt: f64) {
+        self.track_pointers(dt);
+        let mut recognized = Vec::new();
+        self.gestures.advance(dt, self.config.interaction.long_press_ms / 1000.0, &mut recognized);
+        for r in recognized { self.recognized(r); }
+        if let Some((x, y)) = self.held_position() && let Some(fish) = self.fishes.iter_mut().find(|f| f.held && f.is_alive()) {
+            fish.hold_at(x, y, dt);
+            // Nothing is interpolated between steps here, so the fish is drawn right where it is.
+            fish.prev_x = x; fish.prev_y = y;
+        }
+    }
+    /// Whether a press may still turn into a long press, or a fish is held: the tank then needs `advance_input`
+    /// even while it is not animating.
+    pub fn has_pending_input(&self) -> bool { self.grab.is_some() || self.gestures.is_pending() }
+    /// Where the held fish is being dragged to, if a fish is held by a pointer over the tank.
+    fn held_position(&self) -> Option<(f64, f64)> {
+        let grab = self.grab.as_ref()?;
+        let pointer = self.pointers.iter().find(|p| p.id == grab.id)?;
+        Some(((pointer.x + grab.offset_x).clamp(0.0, self.width), (pointer.y + grab.offset_y).clamp(0.0, self.height)))
+    }
+
+    /// Advances the simulation by exactly one fixed step without drawing anything.
+    pub fn update(&mut self) {
+        self.elapsed += FIXED_STEP;
+        let full = self.motion() == Motion::Full;
+        if self.config.layers.god_rays && full { self.update_god_rays(FIXED_STEP); }
+        if self.config.layers.bubbles { self.update_bubbles(if full { FIXED_STEP } else { FIXED_STEP * CALM_BUBBLE_PACE }); }
+        self.update_crabs(FIXED_STEP);
+        self.update_pointers(FIXED_STEP);
+        self.update_fishes(FIXED_STEP);
+        self.update_food(FIXED_STEP);
+    }
+
+    /// Paints the current state of the tank onto `ctx`, interpolated between the last two simulation steps.
+    pub fn draw(&self, ctx: &mut dyn Renderer) {
+        let alpha = self.accumulator / FIXED_STEP;
+        let (palette, layers) = (&self.config.palette, &self.config.layers);
+        self.draw_background(ctx);
+        if self.config.castle.enabled { self.draw_castle(ctx); }
+        if layers.seafloor { self.draw_seafloor(ctx); }
+        if layers.bubbles { for bubble in &self.bubbles { bubble.draw(ctx, alpha, palette); } }
+        if layers.crabs { for crab in &self.crabs { crab.draw(ctx, alpha, palette); } }
+        if layers.fish { for fish in &self.fishes { fish.draw(ctx, alpha); } }
+        if layers.food { self.draw_food(ctx, alpha); }
+        if self.reticle_visible { self.draw_reticle(ctx); }
+    }
+
+    fn castle_center_x(&self) -> f64 { self.width * self.config.castle.position }
+    fn scene_obstacles(&self) -> Vec<Obstacle> {
+        if !self.config.castle.enabled { return Vec::new(); }
+        obstacles::castle(self.castle_center_x(), self.castle_base_y, self.get_castle_scale())
+    }
+    fn bubble_spawn(&self) -> BubbleSpawn {
+        if self.config.castle.enabled { BubbleSpawn::Castle { center_x: self.castle_center_x(), base_y: self.castle_base_y, scale: self.get_castle_scale() } }
+        else { BubbleSpawn::Seafloor { width: self.width, floor_y: self.height * 0.9 } }
+    }
+
+	fn draw_cobblestone(&self, ctx: &mut dyn Renderer, path_def: impl Fn(&mut dyn Renderer)) {
+        let colors = &self.config.palette.castle_stones;
         let mut rng = StdRng::seed_from_u64(self.castle_seed);
         ctx.save();
         path_def(ctx); ctx.stroke();
@@ -249,7 +1134,7 @@ impl Aquarium {
             x += (y as i32 % 20) as f64;
             while x < 150.0 {
                 let stone_w = rng.gen_range(stone_w_min..stone_w_max);
-                ctx.set_fill_style(&JsValue::from_str(colors.choose(&mut rng).unwrap()));
+                ctx.set_fill_color(colors.choose(&mut rng).unwrap());
                 ctx.fill_rect(x + rng.gen_range(-1.0..1.0), y + rng.gen_range(-1.0..1.0), stone_w, stone_h);
                 ctx.stroke_rect(x + rng.gen_range(-1.0..1.0), y + rng.gen_range(-1.0..1.0), stone_w, stone_h);
                 x += stone_w;
@@ -259,12 +1144,12 @@ impl Aquarium {
         ctx.restore();
     }
 
-    fn draw_castle(&self) {
-        let ctx = &self.ctx;
+    fn draw_castle(&self, ctx: &mut dyn Renderer) {
+        let palette = &self.config.palette;
         ctx.save();
-        ctx.translate(self.width * 0.5, self.castle_base_y);
-        ctx.scale(self.get_castle_scale(), self.get_castle_scale()).unwrap();
-        ctx.set_stroke_style(&JsValue::from_str("#212529")); ctx.set_line_width(2.0);
+        ctx.translate(self.castle_center_x(), self.castle_base_y);
+        ctx.scale(self.get_castle_scale(), self.get_castle_scale());
+        ctx.set_stroke_color(&palette.castle_shadow); ctx.set_line_width(2.0);
         self.draw_cobblestone(ctx, |ctx| { ctx.begin_path(); ctx.rect(-140.0, -60.0, 40.0, 60.0); });
         self.draw_cobblestone(ctx, |ctx| { ctx.begin_path(); ctx.rect(10.0, -130.0, 60.0, 130.0); });
         self.draw_cobblestone(ctx, |ctx| { ctx.begin_path(); ctx.rect(70.0, -100.0, 50.0, 100.0); });
@@ -274,13 +1159,13 @@ impl Aquarium {
         self.draw_cobblestone(ctx, |ctx| {
             ctx.begin_path(); ctx.move_to(-50.0, -90.0); ctx.line_to(-50.0, -110.0); ctx.line_to(-40.0, -125.0); ctx.line_to(-30.0, -115.0); ctx.line_to(-20.0, -120.0); ctx.line_to(-20.0, -88.0); ctx.close_path();
         });
-        let draw_crenellations = |ctx: &CanvasRenderingContext2d, x: f64, y: f64, w: f64, n: f64| {
+        let draw_crenellations = |ctx: &mut dyn Renderer, x: f64, y: f64, w: f64, n: f64| {
             let merlon_w = w / n; let merlon_h = merlon_w * 0.8;
-            for i in 0..n as usize { if i % 2 == 0 { ctx.begin_path(); ctx.rect(x + i as f64 * merlon_w, y - merlon_h, merlon_w, merlon_h); ctx.set_fill_style(&JsValue::from_str("#6c757d")); ctx.fill(); ctx.stroke(); } }
+            for i in 0..n as usize { if i % 2 == 0 { ctx.begin_path(); ctx.rect(x + i as f64 * merlon_w, y - merlon_h, merlon_w, merlon_h); ctx.set_fill_color(&palette.castle_stones[0]); ctx.fill(); ctx.stroke(); } }
         };
-        let draw_arched_opening = |ctx: &CanvasRenderingContext2d, x: f64, y: f64, w: f64, h: f64| {
-            ctx.save(); ctx.set_fill_style(&JsValue::from_str("#212529"));
-            ctx.begin_path(); ctx.move_to(x, y); ctx.line_to(x, y - h); ctx.arc(x + w / 2.0, y - h, w / 2.0, std::f64::consts::PI, 0.0).unwrap(); ctx.line_to(x + w, y); ctx.close_path(); ctx.fill();
+        let draw_arched_opening = |ctx: &mut dyn Renderer, x: f64, y: f64, w: f64, h: f64| {
+            ctx.save(); ctx.set_fill_color(&palette.castle_shadow);
+            ctx.begin_path(); ctx.move_to(x, y); ctx.line_to(x, y - h); ctx.arc(x + w / 2.0, y - h, w / 2.0, std::f64::consts::PI, 0.0); ctx.line_to(x + w, y); ctx.close_path(); ctx.fill();
             ctx.restore();
         };
         draw_crenellations(ctx, -140.0, -60.0, 40.0, 4.0);
@@ -289,69 +1174,624 @@ impl Aquarium {
         draw_crenellations(ctx, 10.0, -130.0, 60.0, 5.0);
         draw_crenellations(ctx, 70.0, -100.0, 50.0, 4.0);
         draw_arched_opening(ctx, 80.0, -10.0, 30.0, 40.0);
-        ctx.set_fill_style(&JsValue::from_str("#212529"));
+        ctx.set_fill_color(&palette.castle_shadow);
         ctx.begin_path(); ctx.rect(-40.0, -50.0, 80.0, 50.0); ctx.fill();
-        ctx.begin_path(); ctx.arc(0.0, -50.0, 40.0, std::f64::consts::PI, 0.0).unwrap(); ctx.fill();
+        ctx.begin_path(); ctx.arc(0.0, -50.0, 40.0, std::f64::consts::PI, 0.0); ctx.fill();
         ctx.restore();
     }
 
-    pub fn tick(&mut self) {
-        self.frame_count += 1.0;
-        self.draw_background();
-        self.draw_castle();
-        self.draw_seafloor();
-        self.update_bubbles();
-        self.update_crabs();
-        self.update_fishes();
-        self.update_food();
-    }
-    
-    fn draw_background(&mut self) { 
-        let bg_gradient = self.ctx.create_linear_gradient(0.0, 0.0, 0.0, self.height);
-        bg_gradient.add_color_stop(0.0, "#005c97").unwrap(); bg_gradient.add_color_stop(1.0, "#06223b").unwrap();
-        self.ctx.set_fill_style_canvas_gradient(&bg_gradient); self.ctx.fill_rect(0.0, 0.0, self.width, self.height);
-        for ray in &mut self.god_rays { ray.update(); if ray.life >= ray.max_life { ray.reset(self.width, self.height); } ray.draw(&self.ctx); }
-    }
-    fn draw_seafloor(&self) { 
-        self.ctx.begin_path(); self.ctx.move_to(0.0, self.height * 0.9);
-        let mut x = 0.0; while x < self.width + 10.0 { self.ctx.line_to(x, self.get_seafloor_y_at(x)); x += 10.0; }
-        self.ctx.line_to(self.width, self.height); self.ctx.line_to(0.0, self.height); self.ctx.close_path();
-        self.ctx.set_fill_style(&JsValue::from_str("#c2b280")); self.ctx.fill();
-    }
-    fn update_bubbles(&mut self) {
-        let castle_center_x = self.width * 0.5;
-        let castle_scale = self.get_castle_scale();
-        for bubble in &mut self.bubbles {
-            bubble.update();
-            if bubble.y < -bubble.size { bubble.reset(castle_center_x, self.castle_base_y, castle_scale); }
-            bubble.draw(&self.ctx);
+    fn draw_background(&self, ctx: &mut dyn Renderer) { 
+        let palette = &self.config.palette;
+        ctx.set_fill_linear_gradient(0.0, 0.0, 0.0, self.height, &[(0.0, &palette.water_top), (1.0, &palette.water_bottom)]);
+        ctx.fill_rect(0.0, 0.0, self.width, self.height);
+        let ray_color = parse_css_color(&palette.god_ray).unwrap_or([1.0; 4]);
+        let steady = self.motion() != Motion::Full;
+        if self.config.layers.god_rays { for ray in &self.god_rays { ray.draw(ctx, ray_color, steady); } }
+    }
+    fn draw_seafloor(&self, ctx: &mut dyn Renderer) { 
+        ctx.begin_path(); ctx.move_to(0.0, self.height * 0.9);
+        let mut x = 0.0; while x < self.width + 10.0 { ctx.line_to(x, self.get_seafloor_y_at(x)); x += 10.0; }
+        ctx.line_to(self.width, self.height); ctx.line_to(0.0, self.height); ctx.close_path();
+        ctx.set_fill_color(&self.config.palette.sand); ctx.fill();
+    }
+    fn draw_food(&self, ctx: &mut dyn Renderer, alpha: f64) {
+        ctx.set_fill_color(&self.config.palette.food);
+        for food_item in &self.food { ctx.begin_path(); ctx.arc(food_item.x, lerp(food_item.prev_y, food_item.y, alpha), 3.0, 0.0, std::f64::consts::PI * 2.0); ctx.fill(); }
+    }
+    /// A ring with ticks at the feeding spot, outlined in dark so it shows against light and dark water alike.
+    fn draw_reticle(&self, ctx: &mut dyn Renderer) {
+        let (x, y) = self.reticle;
+        ctx.save();
+        ctx.begin_path(); ctx.arc(x, y, RETICLE_RADIUS, 0.0, std::f64::consts::PI * 2.0);
+        for (dx, dy) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+            ctx.move_to(x + dx * RETICLE_RADIUS * 0.5, y + dy * RETICLE_RADIUS * 0.5); ctx.line_to(x + dx * RETICLE_RADIUS * 1.5, y + dy * RETICLE_RADIUS * 1.5);
+        }
+        ctx.set_stroke_color("rgba(0, 0, 0, 0.5)"); ctx.set_line_width(4.0); ctx.stroke();
+        ctx.set_stroke_color("rgba(255, 255, 255, 0.9)"); ctx.set_line_width(2.0); ctx.stroke();
+        ctx.restore();
+    }
+    /// Turns each pointer's movement during a frame of `dt` seconds into a smoothed velocity. Pointer events arrive
+    /// once per frame, however many steps the frame runs, so the velocity is measured per frame rather than per step.
+    fn track_pointers(&mut self, dt: f64) {
+        if dt <= 0.0 { return; }
+        let follow = 1.0 - (-POINTER_SMOOTHING * dt).exp();
+        for pointer in &mut self.pointers {
+            pointer.vx += (pointer.moved.0 / dt - pointer.vx) * follow; pointer.vy += (pointer.moved.1 / dt - pointer.vy) * follow;
+            pointer.moved = (0.0, 0.0);
         }
     }
-    fn update_food(&mut self) {
+    /// Scatters the fish from pointers moving too fast for their temperament, and fires long presses that have been
+    /// held long enough.
+    fn update_pointers(&mut self, dt: f64) {
+        let startle_above = pointer_reaction(self.config.interaction.temperament).startle_above;
+        let fast: Vec<(f64, f64)> = self.pointers.iter().filter(|p| p.speed() > startle_above).map(|p| (p.x, p.y)).collect();
+        for (x, y) in fast { self.startle_fishes(x, y, self.config.interaction.pointer_radius); }
+        let mut recognized = Vec::new();
+        self.gestures.advance(dt, self.config.interaction.long_press_ms / 1000.0, &mut recognized);
+        for r in recognized { self.recognized(r); }
+    }
+    fn update_god_rays(&mut self, dt: f64) {
+        for ray in &mut self.god_rays { ray.update(dt); if ray.life >= ray.max_life { ray.reset(self.width, self.height, &mut self.rng); } }
+    }
+    fn update_bubbles(&mut self, dt: f64) {
+        let spawn = self.bubble_spawn();
+        let mut surplus = self.bubbles.len().saturating_sub(self.config.bubble_count);
+        for bubble in &mut self.bubbles { bubble.update(dt); }
+        self.bubbles.retain_mut(|bubble| {
+            if bubble.y >= -bubble.size { return true; }
+            self.events.push(TankEvent::BubblePopped { x: bubble.x });
+            if surplus > 0 { surplus -= 1; return false; }
+            bubble.reset(spawn, &mut self.rng); true
+        });
+    }
+    fn update_food(&mut self, dt: f64) {
         let food_floor_ys: Vec<f64> = self.food.iter().map(|f| self.get_seafloor_y_at(f.x)).collect();
         for (i, food_item) in self.food.iter_mut().enumerate() {
             let floor_y = food_floor_ys[i];
-            if food_item.y < floor_y { food_item.vy += 0.007; food_item.vy *= 0.99; food_item.y += food_item.vy; }
+            food_item.prev_y = food_item.y;
+            if food_item.y < floor_y { food_item.vy += FOOD_GRAVITY * dt; food_item.vy *= FOOD_DRAG.powf(dt); food_item.y += food_item.vy * dt; }
             if food_item.y > floor_y { food_item.y = floor_y; }
-            self.ctx.set_fill_style(&JsValue::from_str("#f0e68c"));
-            self.ctx.begin_path(); self.ctx.arc(food_item.x, food_item.y, 3.0, 0.0, std::f64::consts::PI * 2.0).unwrap(); self.ctx.fill();
         }
     }
-    fn update_crabs(&mut self) {
+    fn update_crabs(&mut self, dt: f64) {
         let crab_floor_ys: Vec<f64> = self.crabs.iter().map(|c| self.get_seafloor_y_at(c.x)).collect();
-        for (i, crab) in self.crabs.iter_mut().enumerate() { crab.update(crab_floor_ys[i], self.width); crab.draw(&self.ctx); }
+        for (i, crab) in self.crabs.iter_mut().enumerate() {
+            let state = crab.state;
+            crab.update(crab_floor_ys[i], self.width, dt, &mut self.rng);
+            if crab.state != state { self.events.push(TankEvent::CrabStateChanged { crab: crab.id, state: crab.state, x: crab.x }); }
+        }
+        self.crabs.retain(|crab| !crab.is_gone(self.width));
     }
-    fn update_fishes(&mut self) {
-        let mut food_to_remove = Vec::new();
+    /// Pairs up adults ready to breed and hatches their fry, less often the closer the tank is to its carrying capacity.
+    fn breed_fishes(&mut self, dt: f64) {
+        let capacity = self.config.breeding.carrying_capacity;
+        let mut population = self.fishes.iter().filter(|f| f.is_alive() && !f.leaving).count();
+        let ready: Vec<usize> = (0..self.fishes.len()).filter(|&i| self.fishes[i].can_breed()).collect();
+        // Fish have moved and come and gone since the grid was built for this step, so index the ready ones afresh.
+        self.fish_grid.rebuild(ready.iter().map(|&i| (self.fishes[i].x, self.fishes[i].y)));
+        // Each ready fish gets one chance to find a mate per step, and a fish that has just bred is taken.
+        let mut taken = vec![false; ready.len()];
+        let mut fry = Vec::new();
+        for r in (0..ready.len()).rev() {
+            if population >= capacity { break; }
+            if taken[r] { continue; }
+            taken[r] = true;
+            let (i, a) = (ready[r], &self.fishes[ready[r]]);
+            let mut mate = None;
+            self.fish_grid.for_each_within(a.x, a.y, a.size * MATE_DISTANCE, |k, _, _| {
+                if mate.is_none() && !taken[k] && self.fishes[ready[k]].species == a.species { mate = Some(k); }
+            });
+            let Some(k) = mate else { continue; };
+            let crowding = 1.0 - population as f64 / capacity as f64;
+            if !self.rng.gen_bool((BREEDING_RATE * dt * crowding).clamp(0.0, 1.0)) { continue; }
+            taken[k] = true;
+            let j = ready[k];
+            let brood = self.rng.gen_range(1..=3).min(capacity - population);
+            for _ in 0..brood { fry.push(Fish::fry(&self.fishes[i], &self.fishes[j], &self.config.lifecycle, &mut self.rng)); }
+            population += brood;
+            for parent in [i, j] {
+                let fish = &mut self.fishes[parent];
+                fish.breed_cooldown = self.config.breeding.cooldown_seconds; fish.hunger = (fish.hunger + 0.25).min(1.0);
+            }
+        }
+        for fish in fry { self.spawn_fish(fish, SpawnCause::Hatched); }
+    }
+
+    fn update_fishes(&mut self, dt: f64) {
         let fish_floor_ys: Vec<f64> = self.fishes.iter().map(|f| self.get_seafloor_y_at(f.x)).collect();
+        let pace = if self.motion() == Motion::Full { 1.0 } else { CALM_FISH_PACE };
+        // Leaving, dead and held fish are no longer part of any school, nor hunters or prey.
+        let snapshots: Vec<Option<(Species, f64, Boid)>> = self.fishes.iter().map(|f| (!f.leaving && !f.held && f.is_alive()).then(|| (f.species, f.size, f.boid()))).collect();
+        self.fish_grid.rebuild(self.fishes.iter().map(|f| (f.x, f.y)));
+        self.food_grid.rebuild(self.food.iter().map(|f| (f.x, f.y)));
+        let (mut neighbours, mut threats, mut hunts) = (Vec::new(), Vec::new(), Vec::new());
+        let mut eaten = vec![false; self.food.len()];
+        let flee_radius = self.config.predation.flee_radius;
+        let pointer_radius = self.config.interaction.pointer_radius;
+        let curious_below = pointer_reaction(self.config.interaction.temperament).curious_below;
+        let curious: Vec<(f64, f64)> = self.pointers.iter().filter(|p| self.grab.as_ref().is_none_or(|g| g.id != p.id) && p.speed() < curious_below).map(|p| (p.x, p.y)).collect();
+        let held_at = self.held_position();
         for (i, fish) in self.fishes.iter_mut().enumerate() {
-            fish.update(&self.food, fish_floor_ys[i], self.width, self.height);
-            if let Some((dist_sq, food_idx)) = self.food.iter().enumerate().map(|(i, f)| ((fish.x - f.x).powi(2) + (fish.y - f.y).powi(2), i)).min_by(|a, b| a.0.partial_cmp(&b.0).unwrap()) {
-                if dist_sq < (fish.size + 3.0).powi(2) { food_to_remove.push(food_idx); }
+            let was_alive = fish.is_alive();
+            fish.live(&self.config.lifecycle, dt);
+            if was_alive && !fish.is_alive() { self.events.push(TankEvent::FishDied { fish: fish.id, species: fish.species, x: fish.x, y: fish.y, cause: DeathCause::OldAge }); }
+            if fish.held && fish.is_alive() && let Some((x, y)) = held_at { fish.hold_at(x, y, dt); continue; }
+            neighbours.clear(); threats.clear();
+            self.fish_grid.for_each_within(fish.x, fish.y, NEIGHBOR_RADIUS.max(flee_radius), |j, x, y| {
+                let Some((species, size, b)) = snapshots[j] else { return; };
+                if j == i { return; }
+                if species == fish.species && (x - fish.x).powi(2) + (y - fish.y).powi(2) < NEIGHBOR_RADIUS * NEIGHBOR_RADIUS { neighbours.push(b); }
+                if species.spec().predator && !fish.is_predator() && fish.size < size * PREY_SIZE_RATIO
+                    && (x - fish.x).powi(2) + (y - fish.y).powi(2) < flee_radius * flee_radius { threats.push(b); }
+            });
+            let (mut prey, mut closest_food) = (None, None);
+            if fish.is_predator() {
+                if fish.is_alive() && fish.appetite() > 0.0 {
+                    let mut best: Option<(f64, usize, Boid)> = None;
+                    self.fish_grid.for_each_within(fish.x, fish.y, HUNT_RADIUS, |j, x, y| {
+                        let Some((species, size, b)) = snapshots[j] else { return; };
+                        let dist_sq = (x - fish.x).powi(2) + (y - fish.y).powi(2);
+                        if !species.spec().predator && size < fish.size * PREY_SIZE_RATIO && best.is_none_or(|(d, ..)| dist_sq < d) { best = Some((dist_sq, j, b)); }
+                    });
+                    if let Some((_, j, b)) = best { prey = Some(b); hunts.push((i, j)); }
+                }
+            } else {
+                closest_food = self.food_grid.nearest(fish.x, fish.y).map(|(dist_sq, _, x, y)| (dist_sq, x, y));
+            }
+            // The nearest slow pointer in reach draws the fish's interest.
+            let interest = curious.iter().map(|&(x, y)| (x, y, ((x - fish.x).powi(2) + (y - fish.y).powi(2)).sqrt()))
+                .filter(|&(.., dist)| dist < pointer_radius).min_by(|a, b| a.2.total_cmp(&b.2))
+                .map(|(x, y, dist)| (x, y, CURIOSITY * (1.0 - dist / pointer_radius)));
+            let senses = Senses { closest_food, neighbours: &neighbours, threats: &threats, prey, obstacles: &self.obstacles, interest };
+            fish.update(&senses, &self.config, fish_floor_ys[i], self.width, self.height, pace, dt, &mut self.rng);
+            // Only a fish with an appetite bites, and each pellet feeds one fish.
+            if !fish.is_predator() && fish.is_alive() && fish.appetite() > 0.0 && let Some((dist_sq, food_idx, ..)) = self.food_grid.nearest(fish.x, fish.y)
+                && dist_sq < (fish.size + 3.0).powi(2) && !eaten[food_idx] {
+                eaten[food_idx] = true; fish.eat(&self.config.lifecycle);
+                let pellet = &self.food[food_idx];
+                self.events.push(TankEvent::FoodEaten { fish: fish.id, species: fish.species, x: pellet.x, y: pellet.y });
             }
-            fish.draw(&self.ctx);
         }
-        food_to_remove.sort_unstable(); food_to_remove.dedup();
-        for &index in food_to_remove.iter().rev() { if index < self.food.len() { self.food.remove(index); } }
+        // A fish that dies in the hand is let go of, and sinks like any other.
+        if let Some(fish) = self.fishes.iter_mut().find(|f| f.held) && !fish.is_alive() { fish.held = false; self.grab = None; }
+        // A predator that reaches its prey eats it, or in chase-only mode loses interest as if it had.
+        let mut caught = vec![false; self.fishes.len()];
+        for (i, j) in hunts {
+            let (predator, prey) = (&self.fishes[i], &self.fishes[j]);
+            if caught[j] || (predator.x - prey.x).powi(2) + (predator.y - prey.y).powi(2) > (predator.size * 0.8 + prey.size * 0.5).powi(2) { continue; }
+            if self.config.predation.kills {
+                self.events.push(TankEvent::FishDied { fish: prey.id, species: prey.species, x: prey.x, y: prey.y, cause: DeathCause::Eaten });
+                caught[j] = true; self.fishes[i].eat(&self.config.lifecycle);
+            }
+            self.fishes[i].hunger = 0.0;
+        }
+        let mut caught = caught.into_iter();
+        self.fishes.retain(|fish| !caught.next().unwrap() && !fish.is_gone(self.width));
+        if self.config.breeding.enabled { self.breed_fishes(dt); }
+        let mut eaten = eaten.into_iter();
+        self.food.retain(|_| !eaten.next().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts fills so tests can check that a frame actually painted something.
+    #[derive(Default)]
+    struct CountingRenderer { fills: usize, depth: i32 }
+    impl Renderer for CountingRenderer {
+        fn save(&mut self) { self.depth += 1; }
+        fn restore(&mut self) { self.depth -= 1; }
+        fn translate(&mut self, _: f64, _: f64) {}
+        fn rotate(&mut self, _: f64) {}
+        fn scale(&mut self, _: f64, _: f64) {}
+        fn begin_path(&mut self) {}
+        fn move_to(&mut self, _: f64, _: f64) {}
+        fn line_to(&mut self, _: f64, _: f64) {}
+        fn arc(&mut self, _: f64, _: f64, _: f64, _: f64, _: f64) {}
+        fn ellipse(&mut self, _: f64, _: f64, _: f64, _: f64, _: f64, _: f64, _: f64) {}
+        fn rect(&mut self, _: f64, _: f64, _: f64, _: f64) {}
+        fn close_path(&mut self) {}
+        fn fill(&mut self) { self.fills += 1; }
+        fn stroke(&mut self) {}
+        fn clip(&mut self) {}
+        fn fill_rect(&mut self, _: f64, _: f64, _: f64, _: f64) { self.fills += 1; }
+        fn stroke_rect(&mut self, _: f64, _: f64, _: f64, _: f64) {}
+        fn set_fill_color(&mut self, _: &str) {}
+        fn set_fill_linear_gradient(&mut self, _: f64, _: f64, _: f64, _: f64, _: &[(f64, &str)]) {}
+        fn set_stroke_color(&mut self, _: &str) {}
+        fn set_line_width(&mut self, _: f64) {}
+        fn set_blur(&mut self, _: f64) {}
+        fn set_global_alpha(&mut self, _: f64) {}
+    }
+
+    #[test]
+    fn draws_without_a_canvas() {
+        let mut aquarium = Aquarium::with_size(800.0, 600.0, 7);
+        let mut renderer = CountingRenderer::default();
+        for _ in 0..10 { aquarium.update(); }
+        aquarium.draw(&mut renderer);
+        assert!(renderer.fills > 0);
+        assert_eq!(renderer.depth, 0);
+    }
+
+    #[test]
+    fn a_tank_without_area_is_an_error() {
+        assert!(Aquarium::with_config(0.0, 600.0, 1, AquariumConfig::default()).is_err());
+        assert!(Aquarium::with_config(800.0, -1.0, 1, AquariumConfig::default()).is_err());
+        assert!(Aquarium::with_config(1.0, 1.0, 1, AquariumConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn same_seed_plays_out_identically() {
+        let run = |seed| {
+            let mut aquarium = Aquarium::with_size(800.0, 600.0, seed);
+            aquarium.add_food(400.0, 100.0); aquarium.add_fish(200.0, 200.0);
+            for _ in 0..500 { aquarium.update(); }
+            let fishes: Vec<(f64, f64)> = aquarium.fishes.iter().map(|f| (f.x, f.y)).collect();
+            let crabs: Vec<f64> = aquarium.crabs.iter().map(|c| c.x).collect();
+            (fishes, crabs, aquarium.food.len(), aquarium.castle_seed)
+        };
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+    }
+
+    #[test]
+    fn simulation_speed_does_not_depend_on_frame_rate() {
+        let run = |hz: f64| {
+            let mut aquarium = Aquarium::with_size(800.0, 600.0, 42);
+            for _ in 0..(hz as usize * 2) { aquarium.advance(1.0 / hz); }
+            aquarium.elapsed + aquarium.accumulator
+        };
+        assert!((run(60.0) - 2.0).abs() < 1e-9);
+        assert!((run(144.0) - 2.0).abs() < 1e-9);
+        let mut stalled = Aquarium::with_size(800.0, 600.0, 42);
+        stalled.advance(60.0);
+        assert!(stalled.elapsed <= FIXED_STEP * MAX_STEPS_PER_TICK as f64 + 1e-9);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn resize_keeps_the_scene_inside_the_new_bounds() {
+        let mut aquarium = Aquarium::with_size(1600.0, 900.0, 3);
+        for _ in 0..60 { aquarium.update(); }
+        aquarium.resize(400.0, 300.0);
+        assert!(aquarium.fishes.iter().all(|f| (0.0..=400.0).contains(&f.x) && (0.0..=300.0).contains(&f.y)));
+        assert_eq!(aquarium.god_rays.len(), god_ray_count(400.0, 5.0));
+        assert_eq!(aquarium.castle_base_y, 300.0 * 0.95);
+    }
+
+    #[test]
+    fn schooling_fish_swim_closer_together() {
+        let mean_nearest_distance = |schooling: f64| {
+            let config = AquariumConfig { fish_count: 40, species: vec![Species::Tetra], schooling, ..AquariumConfig::default() };
+            let mut aquarium = Aquarium::with_config(1200.0, 800.0, 9, config).unwrap();
+            for _ in 0..60 * 30 { aquarium.update(); }
+            let fishes = &aquarium.fishes;
+            fishes.iter().enumerate().map(|(i, a)| {
+                fishes.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, b)| ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()).fold(f64::MAX, f64::min)
+            }).sum::<f64>() / fishes.len() as f64
+        };
+        let (schooling, solitary) = (mean_nearest_distance(1.0), mean_nearest_distance(0.0));
+        assert!(schooling < solitary * 0.8, "schooling {schooling:.1}px vs solitary {solitary:.1}px");
+    }
+
+    #[test]
+    fn fish_eat_until_sated_grow_and_die_of_old_age() {
+        let lifecycle = LifecycleConfig { natural_death: true, lifespan_seconds: 60.0, ..LifecycleConfig::default() };
+        let config = AquariumConfig { fish_count: 1, lifecycle, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 4, config).unwrap();
+        let fish = &mut aquarium.fishes[0];
+        fish.hunger = 1.0; fish.age = 0.0;
+        let (x, y, adult_size) = (fish.x, fish.y, fish.adult_size);
+        for _ in 0..5 { aquarium.add_food(x, y); }
+        // One bite per step, and the fish stops biting once it is sated.
+        for _ in 0..10 { aquarium.update(); }
+        assert!(aquarium.fishes[0].appetite() == 0.0 && aquarium.food.len() == 2, "{} pellets left", aquarium.food.len());
+        assert!(aquarium.fishes[0].size > adult_size);
+        for _ in 0..60 * 80 { aquarium.update(); }
+        assert!(aquarium.fishes.is_empty());
+    }
+
+    #[test]
+    fn predators_only_kill_when_allowed() {
+        let survivors = |kills: bool| {
+            let predation = config::PredationConfig { kills, ..config::PredationConfig::default() };
+            let config = AquariumConfig { fish_count: 2, species: vec![Species::Barracuda], predation, ..AquariumConfig::default() };
+            let mut aquarium = Aquarium::with_config(800.0, 600.0, 12, config).unwrap();
+            for i in 0..8 { aquarium.add_fish_of(Species::Tetra, 100.0 + i as f64 * 80.0, 250.0); }
+            let mut fled = false;
+            for _ in 0..60 * 60 {
+                for fish in aquarium.fishes.iter_mut().filter(|f| f.is_predator()) { fish.hunger = 1.0; }
+                aquarium.update();
+                fled |= aquarium.fishes.iter().any(|f| !f.is_predator() && f.vx.hypot(f.vy) > f.max_speed + 1.0);
+            }
+            assert!(fled, "prey never sped up to flee");
+            aquarium.fishes.iter().filter(|f| f.species == Species::Tetra).count()
+        };
+        assert_eq!(survivors(false), 8);
+        assert!(survivors(true) < 8);
+    }
+
+    #[test]
+    fn population_changes_are_gradual() {
+        let mut aquarium = Aquarium::with_size(800.0, 600.0, 5);
+        aquarium.set_fish_count(5).unwrap();
+        assert_eq!(aquarium.fishes.len(), 15);
+        assert_eq!(aquarium.fishes.iter().filter(|f| f.leaving).count(), 10);
+        for _ in 0..60 * 120 { aquarium.update(); }
+        assert_eq!(aquarium.fishes.len(), 5);
+        aquarium.set_fish_count(8).unwrap();
+        assert!(aquarium.fishes[5..].iter().all(|f| f.x < 0.0 || f.x > 800.0));
+        aquarium.set_bubble_count(0).unwrap();
+        for _ in 0..60 * 60 { aquarium.update(); }
+        assert!(aquarium.bubbles.is_empty());
+        // A fish fading out after its death is not one of the fish asked for.
+        aquarium.fishes[0].dead_for = Some(0.0);
+        aquarium.set_fish_count(8).unwrap();
+        assert_eq!(aquarium.fishes.iter().filter(|f| f.is_alive() && !f.leaving).count(), 8);
+    }
+
+    #[test]
+    fn leaving_crabs_keep_walking_off_the_nearer_edge() {
+        let config = AquariumConfig { fish_count: 0, crab_count: 1, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 16, config).unwrap();
+        aquarium.crabs[0].x = 40.0;
+        aquarium.set_crab_count(0).unwrap();
+        for _ in 0..60 * 10 {
+            aquarium.update();
+            assert!(aquarium.crab_snapshots().iter().all(|c| c.vx < 0.0));
+        }
+        assert!(aquarium.crabs.is_empty());
+    }
+
+    #[test]
+    fn fish_swim_around_the_castle_and_into_its_gate() {
+        let config = AquariumConfig { fish_count: 40, species: vec![Species::Clownfish, Species::Eel], ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(1200.0, 800.0, 6, config).unwrap();
+        let (center_x, base_y, scale) = (aquarium.castle_center_x(), aquarium.castle_base_y, aquarium.get_castle_scale());
+        let mut visited_gate = false;
+        for _ in 0..60 * 120 {
+            aquarium.update();
+            for fish in &aquarium.fishes {
+                assert!(!aquarium.obstacles.iter().any(|o| o.contains(fish.x, fish.y)), "fish inside the castle at ({}, {})", fish.x, fish.y);
+                let (x, y) = ((fish.x - center_x) / scale, (fish.y - base_y) / scale);
+                visited_gate |= x.abs() < 30.0 && y > -80.0;
+            }
+        }
+        assert!(visited_gate);
+    }
+
+    #[test]
+    fn tails_beat_faster_with_speed_and_in_a_burst() {
+        let advance = |speed: f64, seek_w: f64| { let mut swim = Swim::default(); swim.update(speed, 0.0, 10.0, seek_w, 1.0, 0.01); swim.phase };
+        assert!(advance(0.0, 0.0) > 0.0);
+        assert!(advance(40.0, 0.0) > advance(0.0, 0.0) && advance(80.0, 0.0) > advance(40.0, 0.0));
+        // Going for food sets off a burst, which beats faster still and swings the tail wider.
+        assert!(advance(40.0, 1.0) > advance(40.0, 0.0));
+        let mut swim = Swim::default();
+        swim.update(40.0, 0.0, 10.0, 1.0, 1.0, 0.01);
+        assert!(swim.burst > 0.9 && swim.pose(1.0).amplitude > Swim::default().pose(1.0).amplitude);
+    }
+
+    #[test]
+    fn added_fish_are_of_the_requested_species() {
+        let config = AquariumConfig { fish_count: 0, species: vec![Species::Tetra], ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 18, config).unwrap();
+        aquarium.add_fish_of(Species::Pufferfish, 200.0, 300.0);
+        aquarium.add_fish_of(Species::from_name("eel").unwrap(), 400.0, 300.0);
+        aquarium.add_fish(600.0, 300.0);
+        let species: Vec<Species> = aquarium.fish_snapshots().iter().map(|f| f.species).collect();
+        assert_eq!(species, [Species::Pufferfish, Species::Eel, Species::Tetra]);
+        let (min, max) = Species::Pufferfish.spec().size;
+        assert!((min..=max).contains(&aquarium.fishes[0].size));
+        assert_eq!((aquarium.fishes[0].x, aquarium.fishes[0].y), (200.0, 300.0));
+    }
+
+    #[test]
+    fn hiding_the_castle_clears_the_way_for_the_fish() {
+        let config = AquariumConfig { castle: config::CastleConfig { enabled: false, ..config::CastleConfig::default() }, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(1200.0, 800.0, 7, config).unwrap();
+        assert!(aquarium.obstacles.is_empty());
+        aquarium.set_layer_visible("castle", true).unwrap();
+        assert!(!aquarium.obstacles.is_empty());
+        aquarium.set_layer_visible("castle", false).unwrap();
+        assert!(aquarium.obstacles.is_empty());
+        aquarium.set_layer_visible("castle", true).unwrap();
+        let (x, y) = (aquarium.castle_center_x() + 100.0 * aquarium.get_castle_scale(), aquarium.castle_base_y - 50.0 * aquarium.get_castle_scale());
+        assert!(aquarium.obstacles.iter().any(|o| o.contains(x, y)));
+    }
+
+    #[test]
+    fn friendly_fish_gather_at_a_still_pointer_and_scatter_from_a_tap() {
+        let tank = |temperament| {
+            let interaction = config::InteractionConfig { temperament, ..config::InteractionConfig::default() };
+            let config = AquariumConfig { fish_count: 30, species: vec![Species::Tetra], interaction, ..AquariumConfig::default() };
+            let mut aquarium = Aquarium::with_config(800.0, 600.0, 9, config).unwrap();
+            aquarium.pointer_move(PointerInput::mouse(400.0, 300.0));
+            for _ in 0..60 * 20 { aquarium.update(); }
+            aquarium
+        };
+        let near = |aquarium: &Aquarium, radius: f64| aquarium.fishes.iter().filter(|f| (f.x - 400.0).powi(2) + (f.y - 300.0).powi(2) < radius * radius).count();
+        let (mut friendly, indifferent) = (tank(Temperament::Friendly), tank(Temperament::Indifferent));
+        assert!(near(&friendly, 100.0) > near(&indifferent, 100.0) + 5, "{} vs {}", near(&friendly, 100.0), near(&indifferent, 100.0));
+        let gathered = near(&friendly, 40.0);
+        friendly.tap_glass(400.0, 300.0);
+        assert!(friendly.fishes.iter().filter(|f| f.startle.is_some()).count() >= near(&friendly, 100.0));
+        for _ in 0..30 { friendly.update(); }
+        assert!(near(&friendly, 40.0) * 5 < gathered, "{} of {} still close", near(&friendly, 40.0), gathered);
+    }
+
+    #[test]
+    fn pointer_velocity_is_measured_per_frame() {
+        let config = AquariumConfig { fish_count: 0, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 19, config).unwrap();
+        // 10 pixels per frame at 20 fps, each frame running three steps: 200 pixels per second throughout.
+        for frame in 0..60 {
+            aquarium.pointer_move(PointerInput::mouse(100.0 + frame as f64 * 10.0, 300.0));
+            aquarium.advance(FIXED_STEP * 3.0);
+            if frame > 20 { assert!((aquarium.pointers[0].vx - 200.0).abs() < 5.0, "{} px/s", aquarium.pointers[0].vx); }
+        }
+    }
+
+    #[test]
+    fn the_topmost_fish_can_be_dragged_and_thrown() {
+        let config = AquariumConfig { fish_count: 0, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 10, config).unwrap();
+        aquarium.add_fish_of(Species::Clownfish, 300.0, 300.0);
+        aquarium.add_fish_of(Species::Clownfish, 302.0, 300.0);
+        assert!(!aquarium.pointer_down(PointerInput::mouse(500.0, 300.0)));
+        assert!(aquarium.pointer_down(PointerInput::mouse(301.0, 300.0)));
+        // The fish drawn last was on top; it stays on top while held.
+        assert!(aquarium.fishes[1].held && aquarium.fishes[1].x == 302.0);
+        for step in 1..=30 { aquarium.pointer_move(PointerInput::mouse(301.0 + step as f64 * 5.0, 300.0)); aquarium.advance(FIXED_STEP); }
+        let held = &aquarium.fishes[1];
+        assert!((held.x - 452.0).abs() < 1e-9 && held.y == 300.0 && held.swim.burst > 0.9, "held at ({}, {})", held.x, held.y);
+        aquarium.pointer_up(PointerInput::mouse(451.0, 300.0));
+        assert!(!aquarium.is_holding_fish() && !aquarium.fishes[1].held);
+        assert!(aquarium.fishes[1].vx > 200.0, "thrown at {}", aquarium.fishes[1].vx);
+        for _ in 0..30 { aquarium.update(); }
+        assert!(aquarium.fishes[1].x > 500.0);
+        // A fish that dies while held is dropped.
+        aquarium.config.lifecycle.natural_death = true;
+        let (x, y) = (aquarium.fishes[1].x, aquarium.fishes[1].y);
+        assert!(aquarium.pointer_down(PointerInput::mouse(x, y)));
+        aquarium.fishes[1].lifespan = 0.0;
+        aquarium.update();
+        assert!(!aquarium.is_holding_fish() && !aquarium.fishes[1].held && !aquarium.fishes[1].is_alive());
+    }
+
+    #[test]
+    fn fingers_feed_together_and_a_long_press_adds_a_fish() {
+        let config = AquariumConfig { fish_count: 0, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 11, config).unwrap();
+        let finger = |id, x| PointerInput { id, kind: input::PointerKind::Touch, x, y: 200.0, button: 0, pressure: 0.5 };
+        aquarium.pointer_down(finger(1, 100.0));
+        aquarium.pointer_down(finger(2, 300.0));
+        aquarium.pointer_down(finger(3, 500.0));
+        aquarium.update();
+        aquarium.pointer_up(finger(1, 100.0));
+        aquarium.pointer_up(finger(2, 300.0));
+        assert_eq!(aquarium.food.len(), 2);
+        assert!(aquarium.fishes.is_empty() && aquarium.captures_context_menu());
+        for _ in 0..60 { aquarium.update(); }
+        assert_eq!(aquarium.fishes.len(), 1);
+        // The long press has been used up, so lifting the finger adds no food.
+        aquarium.pointer_up(finger(3, 500.0));
+        assert_eq!(aquarium.food.len(), 2);
+    }
+
+    #[test]
+    fn keys_feed_add_fish_and_move_the_feeding_spot() {
+        let config = AquariumConfig { fish_count: 2, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 12, config).unwrap();
+        assert_eq!(aquarium.key_down("n"), Some(KeyCommand::AddFish));
+        assert_eq!(aquarium.take_announcement().as_deref(), Some("Fish added, 3 in the tank"));
+        assert_eq!(aquarium.take_announcement(), None);
+        aquarium.key_down("F");
+        for _ in 0..3 { aquarium.key_down("ArrowLeft"); }
+        aquarium.key_down("ArrowDown");
+        assert_eq!(aquarium.take_announcement().as_deref(), Some("Feeding spot 41% across, 54% down"));
+        aquarium.key_down("Enter");
+        assert_eq!((aquarium.food.len(), aquarium.food[1].x, aquarium.food[1].y), (2, 325.0, 325.0));
+        assert_eq!(aquarium.key_down(" "), Some(KeyCommand::Pause));
+        assert_eq!(aquarium.key_down("q"), None);
+    }
+
+    #[test]
+    fn reduced_motion_calms_the_tank_and_still_motion_freezes_it() {
+        let mean_speed = |reduced| {
+            let mut aquarium = Aquarium::with_config(800.0, 600.0, 13, AquariumConfig::default()).unwrap();
+            aquarium.set_prefers_reduced_motion(reduced);
+            let (floor, ray_life) = (aquarium.get_seafloor_y_at(100.0), aquarium.god_rays[0].life);
+            for _ in 0..60 * 10 { aquarium.update(); }
+            assert_eq!(reduced, (aquarium.get_seafloor_y_at(100.0), aquarium.god_rays[0].life) == (floor, ray_life));
+            aquarium.fishes.iter().map(|f| f.vx.hypot(f.vy)).sum::<f64>() / aquarium.fishes.len() as f64
+        };
+        let (full, calm) = (mean_speed(false), mean_speed(true));
+        assert!(calm < full * 0.7, "{} vs {}", calm, full);
+
+        let config = AquariumConfig { motion: Motion::Still, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 13, config).unwrap();
+        let before: Vec<_> = aquarium.fishes.iter().map(|f| (f.x, f.y)).collect();
+        aquarium.advance(5.0);
+        assert_eq!(aquarium.fishes.iter().map(|f| (f.x, f.y)).collect::<Vec<_>>(), before);
+    }
+
+    #[test]
+    fn a_still_tank_still_drags_fish_and_recognises_long_presses() {
+        let config = AquariumConfig { fish_count: 0, motion: Motion::Still, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 17, config).unwrap();
+        aquarium.add_fish_of(Species::Clownfish, 300.0, 300.0);
+        assert!(aquarium.pointer_down(PointerInput::mouse(301.0, 300.0)) && aquarium.has_pending_input());
+        aquarium.pointer_move(PointerInput::mouse(401.0, 250.0));
+        aquarium.advance(FIXED_STEP);
+        assert_eq!((aquarium.fishes[0].x, aquarium.fishes[0].y), (400.0, 250.0));
+        aquarium.pointer_up(PointerInput::mouse(401.0, 250.0));
+        assert!(!aquarium.has_pending_input());
+        let finger = PointerInput { id: 2, kind: input::PointerKind::Touch, x: 600.0, y: 200.0, button: 0, pressure: 0.5 };
+        aquarium.pointer_down(finger);
+        for _ in 0..40 { aquarium.advance(FIXED_STEP); }
+        assert_eq!(aquarium.fishes.len(), 2);
+        assert!(!aquarium.has_pending_input());
+        aquarium.pointer_up(finger);
+    }
+
+    #[test]
+    fn events_are_collected_only_while_listening() {
+        let config = AquariumConfig { fish_count: 0, crab_count: 2, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 14, config).unwrap();
+        aquarium.add_food(100.0, 100.0);
+        assert!(aquarium.take_events().is_empty());
+        aquarium.listen_for_events(true);
+        aquarium.add_fish_of(Species::Tetra, 400.0, 300.0);
+        for fish in &mut aquarium.fishes { fish.hunger = 1.0; }
+        aquarium.add_food(400.0, 280.0);
+        let events = aquarium.take_events();
+        assert!(matches!(events[0], TankEvent::FishSpawned { fish: 3, species: Species::Tetra, cause: SpawnCause::Added, .. }));
+        assert_eq!(events[1], TankEvent::FoodDropped { x: 400.0, y: 280.0 });
+        for _ in 0..60 * 20 { aquarium.update(); }
+        let events = aquarium.take_events();
+        assert!(events.iter().any(|e| matches!(e, TankEvent::FoodEaten { fish: 3, .. })));
+        assert!(events.iter().any(|e| matches!(e, TankEvent::CrabStateChanged { crab: 1 | 2, .. })));
+        assert!(events.iter().any(|e| matches!(e, TankEvent::BubblePopped { .. })));
+    }
+
+    #[test]
+    fn snapshots_and_hit_tests_report_what_is_in_the_tank() {
+        let config = AquariumConfig { fish_count: 0, crab_count: 1, bubble_count: 0, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 15, config).unwrap();
+        aquarium.add_fish_of(Species::Clownfish, 300.0, 300.0);
+        aquarium.add_fish_of(Species::Angelfish, 302.0, 300.0);
+        aquarium.add_food(500.0, 100.0);
+        assert_eq!(aquarium.counts(), Counts { fish: 2, food: 1, crabs: 1, bubbles: 0 });
+        let fish = aquarium.fish_snapshots();
+        assert_eq!((fish[0].id, fish[0].species, fish[0].state), (2, Species::Clownfish, FishState::Swimming));
+        assert_eq!(aquarium.fish_snapshot(3).map(|f| f.x), Some(302.0));
+        // The fish added last is drawn on top.
+        assert!(matches!(aquarium.entity_at(301.0, 300.0), Some(EntitySnapshot::Fish(FishSnapshot { id: 3, .. }))));
+        assert!(matches!(aquarium.entity_at(501.0, 101.0), Some(EntitySnapshot::Food(_))));
+        let crab = &aquarium.crab_snapshots()[0];
+        assert!(matches!(aquarium.entity_at(crab.x, crab.y - 2.0), Some(EntitySnapshot::Crab(CrabSnapshot { id: 1, .. }))));
+        assert_eq!(aquarium.entity_at(700.0, 50.0), None);
+    }
+
+    #[test]
+    fn well_fed_fish_breed_up_to_the_carrying_capacity() {
+        let breeding = config::BreedingConfig { enabled: true, carrying_capacity: 12, cooldown_seconds: 10.0 };
+        let config = AquariumConfig { fish_count: 6, species: vec![Species::Tetra], breeding, ..AquariumConfig::default() };
+        let mut aquarium = Aquarium::with_config(800.0, 600.0, 8, config).unwrap();
+        let mut saw_fry = false;
+        for _ in 0..60 * 240 {
+            for fish in &mut aquarium.fishes { fish.hunger = 0.0; }
+            aquarium.update();
+            saw_fry |= aquarium.fishes.iter().any(|f| !f.is_adult());
+            assert!(aquarium.fishes.len() <= 12);
+        }
+        assert!(saw_fry);
+        assert_eq!(aquarium.fishes.len(), 12);
+        // Fry grow up into adults within a couple of minutes.
+        for _ in 0..60 * 120 { aquarium.update(); }
+        assert!(aquarium.fishes.iter().all(|f| f.is_adult()));
+    }
+}
@@ -0,0 +1,116 @@
+// crates/aquarium/src/obstacles.rs
+
+/// A solid polygon in tank coordinates that fish steer around and cannot swim into.
+pub struct Obstacle { points: Vec<(f64, f64)>, min: (f64, f64), max: (f64, f64) }
+
+/// A feeler hit: how far along the feeler (0 at its start, 1 at its tip) and the unit normal of the edge it struck,
+/// facing back towards the feeler's start.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit { pub t: f64, pub normal: (f64, f64) }
+
+impl Obstacle {
+    /// `points` are the polygon's corners in order; it need not be convex.
+    pub fn new(points: Vec<(f64, f64)>) -> Self {
+        let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for &(x, y) in &points { min = (min.0.min(x), min.1.min(y)); max = (max.0.max(x), max.1.max(y)); }
+        Self { points, min, max }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+        self.points.iter().zip(self.points.iter().cycle().skip(1)).map(|(&a, &b)| (a, b))
+    }
+
+    /// Even-odd test of whether `(x, y)` lies inside the polygon.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        if x < self.min.0 || x > self.max.0 || y < self.min.1 || y > self.max.1 { return false; }
+        let mut inside = false;
+        for ((ax, ay), (bx, by)) in self.edges() {
+            if (ay > y) != (by > y) && x < ax + (y - ay) / (by - ay) * (bx - ax) { inside = !inside; }
+        }
+        inside
+    }
+
+    /// The first edge crossed by the feeler from `(x, y)` to `(x + dx, y + dy)`, if any.
+    pub fn cast(&self, x: f64, y: f64, dx: f64, dy: f64) -> Option<Hit> {
+        let (end_x, end_y) = (x + dx, y + dy);
+        if x.max(end_x) < self.min.0 || x.min(end_x) > self.max.0 || y.max(end_y) < self.min.1 || y.min(end_y) > self.max.1 { return None; }
+        let mut best: Option<Hit> = None;
+        for ((ax, ay), (bx, by)) in self.edges() {
+            let (ex, ey) = (bx - ax, by - ay);
+            let denom = dx * ey - dy * ex;
+            if denom == 0.0 { continue; }
+            // Solve (x, y) + t (dx, dy) = a + u e for the feeler's `t` and the edge's `u`.
+            let t = ((ax - x) * ey - (ay - y) * ex) / denom;
+            let u = ((ax - x) * dy - (ay - y) * dx) / denom;
+            if !((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)) || best.is_some_and(|b| b.t <= t) { continue; }
+            let len = (ex * ex + ey * ey).sqrt();
+            let (mut nx, mut ny) = (-ey / len, ex / len);
+            if nx * dx + ny * dy > 0.0 { nx = -nx; ny = -ny; }
+            best = Some(Hit { t, normal: (nx, ny) });
+        }
+        best
+    }
+
+    /// The point on the polygon's outline closest to `(x, y)`.
+    pub fn nearest_boundary_point(&self, x: f64, y: f64) -> (f64, f64) {
+        let mut best = (f64::MAX, (x, y));
+        for ((ax, ay), (bx, by)) in self.edges() {
+            let (ex, ey) = (bx - ax, by - ay);
+            let u = (((x - ax) * ex + (y - ay) * ey) / (ex * ex + ey * ey)).clamp(0.0, 1.0);
+            let (px, py) = (ax + ex * u, ay + ey * u);
+            let dist_sq = (px - x).powi(2) + (py - y).powi(2);
+            if dist_sq < best.0 { best = (dist_sq, (px, py)); }
+        }
+        best.1
+    }
+}
+
+/// Steps along the arch over the castle gate, from its right foot up to where the main tower's wall meets it.
+const GATE_ARCH_STEPS: usize = 8;
+
+/// The solid stonework of the castle drawn by `Aquarium::draw_castle`, in castle space (origin at the middle of its
+/// base, y up negative, before `get_castle_scale`), as one outline on each side of the gate. The gate under the big
+/// arch is left open, and so is the gap in the roof above it, so fish can swim in and out of the castle there. The
+/// small windows are too shallow to enter and count as solid wall.
+fn castle_outlines() -> [Vec<(f64, f64)>; 2] {
+    let (arch_x, arch_y, arch_r): (f64, f64, f64) = (0.0, -50.0, 40.0);
+    // The main tower's wall starts at x = 10, so the arch runs from angle 0 to where its x drops to 10.
+    let end_angle = ((10.0 - arch_x) / arch_r).acos();
+    // Left tower, keep and turret, whose corner overhangs the gate.
+    let keep = vec![
+        (-140.0, 0.0), (-140.0, -60.0), (-100.0, -60.0), (-100.0, -80.0), (-50.0, -90.0), (-50.0, -110.0), (-40.0, -125.0),
+        (-30.0, -115.0), (-20.0, -120.0), (-20.0, -88.0), (-40.0, -89.2), (-40.0, 0.0),
+    ];
+    // Main and right towers, with the arch cut out of the main tower's foot.
+    let mut towers = vec![(10.0, -130.0), (70.0, -130.0), (70.0, -100.0), (120.0, -100.0), (120.0, 0.0), (40.0, 0.0)];
+    towers.extend((0..=GATE_ARCH_STEPS).map(|i| {
+        let angle = end_angle * i as f64 / GATE_ARCH_STEPS as f64;
+        (arch_x + arch_r * angle.cos(), arch_y - arch_r * angle.sin())
+    }));
+    [keep, towers]
+}
+
+/// The castle's obstacles for a castle whose base is centered on `(center_x, base_y)`, drawn at `scale`.
+pub fn castle(center_x: f64, base_y: f64, scale: f64) -> Vec<Obstacle> {
+    castle_outlines().into_iter().map(|outline| Obstacle::new(outline.into_iter().map(|(x, y)| (center_x + x * scale, base_y + y * scale)).collect())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feelers_hit_the_nearest_edge_and_the_gate_stays_open() {
+        let square = Obstacle::new(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        assert!(square.contains(5.0, 5.0) && !square.contains(15.0, 5.0));
+        assert_eq!(square.cast(-10.0, 5.0, 20.0, 0.0), Some(Hit { t: 0.5, normal: (-1.0, 0.0) }));
+        assert_eq!(square.cast(-10.0, 5.0, 5.0, 0.0), None);
+        assert_eq!(square.nearest_boundary_point(5.0, 8.0), (5.0, 10.0));
+
+        let castle = castle(500.0, 900.0, 1.5);
+        let solid = |x: f64, y: f64| castle.iter().any(|o| o.contains(500.0 + x * 1.5, 900.0 + y * 1.5));
+        assert!(solid(-120.0, -30.0) && solid(40.0, -120.0) && solid(100.0, -50.0));
+        // Under the arch, and down through the roof into it.
+        assert!(!solid(0.0, -30.0) && !solid(-10.0, -70.0) && !solid(0.0, -95.0));
+    }
+}
@@ -0,0 +1,100 @@
+// crates/aquarium/src/renderer.rs
+
+/// The drawing surface every aquarium entity paints onto.
+///
+/// The method set mirrors the subset of the Canvas 2D API the aquarium uses, so the
+/// web backend is a thin forwarding layer and other backends only have to
+/// reproduce these semantics. Colors are CSS color strings.
+pub trait Renderer {
+    fn save(&mut self);
+    fn restore(&mut self);
+    fn translate(&mut self, x: f64, y: f64);
+    fn rotate(&mut self, angle: f64);
+    fn scale(&mut self, x: f64, y: f64);
+
+    fn begin_path(&mut self);
+    fn move_to(&mut self, x: f64, y: f64);
+    fn line_to(&mut self, x: f64, y: f64);
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64);
+    #[allow(clippy::too_many_arguments)]
+    fn ellipse(&mut self, x: f64, y: f64, radius_x: f64, radius_y: f64, rotation: f64, start_angle: f64, end_angle: f64);
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64);
+    fn close_path(&mut self);
+
+    fn fill(&mut self);
+    fn stroke(&mut self);
+    /// Intersects the clip region with the current path.
+    fn clip(&mut self);
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64);
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64);
+
+    fn set_fill_color(&mut self, color: &str);
+    /// Fills with a linear gradient from `(x0, y0)` to `(x1, y1)`; `stops` are `(offset, color)` pairs.
+    fn set_fill_linear_gradient(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, stops: &[(f64, &str)]);
+    fn set_stroke_color(&mut self, color: &str);
+    fn set_line_width(&mut self, width: f64);
+    /// Gaussian blur applied to subsequent fills and strokes; `0.0` disables it.
+    fn set_blur(&mut self, radius: f64);
+    /// Opacity multiplied into subsequent fills and strokes, in `0.0..=1.0`.
+    fn set_global_alpha(&mut self, alpha: f64);
+}
+
+/// Parses the CSS color syntaxes the aquarium uses (`#rgb`, `#rrggbb`, `rgb()`, `rgba()`, `hsl()`, `hsla()` and a
+/// few named colors) into straight RGBA components in `0.0..=1.0`.
+pub fn parse_css_color(color: &str) -> Option<[f32; 4]> {
+    let color = color.trim().to_ascii_lowercase();
+    match color.as_str() {
+        "white" => return Some([1.0, 1.0, 1.0, 1.0]),
+        "black" => return Some([0.0, 0.0, 0.0, 1.0]),
+        "transparent" => return Some([0.0, 0.0, 0.0, 0.0]),
+        _ => {}
+    }
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>()?;
+        let [r, g, b] = match digits.len() {
+            3 => [digits[0] * 17, digits[1] * 17, digits[2] * 17],
+            6 => [digits[0] * 16 + digits[1], digits[2] * 16 + digits[3], digits[4] * 16 + digits[5]],
+            _ => return None,
+        };
+        return Some([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]);
+    }
+    let (name, args) = color.strip_suffix(')')?.split_once('(')?;
+    let args: Vec<&str> = args.split(',').map(str::trim).collect();
+    if args.len() != 3 && args.len() != 4 { return None; }
+    // `f32` parsing also accepts `nan` and `inf`, which are no CSS numbers.
+    let number = |s: &str| s.trim_end_matches('%').trim_end_matches("deg").parse::<f32>().ok().filter(|v| v.is_finite());
+    let alpha = match args.get(3) { Some(a) => number(a)?.clamp(0.0, 1.0), None => 1.0 };
+    match name {
+        "rgb" | "rgba" => {
+            let channel = |s: &str| number(s).map(|v| (if s.ends_with('%') { v / 100.0 } else { v / 255.0 }).clamp(0.0, 1.0));
+            Some([channel(args[0])?, channel(args[1])?, channel(args[2])?, alpha])
+        }
+        "hsl" | "hsla" => {
+            let h = number(args[0])?.rem_euclid(360.0) / 60.0;
+            let s = (number(args[1])? / 100.0).clamp(0.0, 1.0);
+            let l = (number(args[2])? / 100.0).clamp(0.0, 1.0);
+            let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+            let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+            let (r, g, b) = match h as u32 { 0 => (c, x, 0.0), 1 => (x, c, 0.0), 2 => (0.0, c, x), 3 => (0.0, x, c), 4 => (x, 0.0, c), _ => (c, 0.0, x) };
+            let m = l - c / 2.0;
+            Some([r + m, g + m, b + m, alpha])
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_color_forms_used_by_the_scene() {
+        assert_eq!(parse_css_color("#fff"), Some([1.0, 1.0, 1.0, 1.0]));
+        assert_eq!(parse_css_color("#ff0000"), Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(parse_css_color("rgba(255, 0, 0, 0.5)"), Some([1.0, 0.0, 0.0, 0.5]));
+        assert_eq!(parse_css_color("hsl(120, 100%, 50%)"), Some([0.0, 1.0, 0.0, 1.0]));
+        assert_eq!(parse_css_color("not a color"), None);
+        assert_eq!(parse_css_color("rgb(nan, 0, 0)"), None);
+        assert_eq!(parse_css_color("rgba(0, 0, 0, inf)"), None);
+    }
+}
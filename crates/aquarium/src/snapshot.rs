@@ -0,0 +1,37 @@
+// crates/aquarium/src/snapshot.rs
+use serde::Serialize;
+use crate::events::CrabState;
+use crate::species::Species;
+
+/// How many of each thing are in the tank, including fish and crabs on their way out and fish fading after death.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Counts { pub fish: usize, pub food: usize, pub crabs: usize, pub bubbles: usize }
+
+/// What a fish is up to, most pressing first: a fish being dragged is `held` even if it was `feeding` before.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FishState { Dead, Held, Leaving, Startled, Feeding, Swimming }
+
+/// A fish as of the last simulation step. `id` is the same one events carry; `hunger` runs from `0` just fed to `1`
+/// starving, and `age` is in seconds.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FishSnapshot {
+    pub id: u32, pub species: Species, pub x: f64, pub y: f64, pub vx: f64, pub vy: f64, pub size: f64,
+    pub state: FishState, pub hunger: f64, pub age: f64, pub adult: bool, pub color: String,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CrabSnapshot { pub id: u32, pub x: f64, pub y: f64, pub vx: f64, pub size: f64, pub state: CrabState, pub leaving: bool }
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct FoodSnapshot { pub x: f64, pub y: f64, pub vy: f64 }
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct BubbleSnapshot { pub x: f64, pub y: f64, pub vy: f64, pub size: f64 }
+
+/// Whatever is found at a point, tagged with its `kind`: `fish`, `crab`, `food` or `bubble`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum EntitySnapshot { Fish(FishSnapshot), Crab(CrabSnapshot), Food(FoodSnapshot), Bubble(BubbleSnapshot) }
@@ -0,0 +1,130 @@
+// crates/aquarium/src/spatial.rs
+
+/// Uniform grid over a set of points, rebuilt from scratch each simulation step.
+///
+/// Points are bucketed by cell with a counting sort, so a rebuild is two linear passes and needs no allocation once
+/// the buffers have grown. The grid covers exactly the bounding box of its points, which keeps lookups exact even
+/// for entities outside the tank (fish swimming in or out, say).
+pub struct SpatialGrid {
+    base_cell_size: f64, cell_size: f64, origin: (f64, f64), cols: usize, rows: usize,
+    /// `entries[cell_start[c]..cell_start[c + 1]]` are the points in cell `c`, as `(index, x, y)`.
+    cell_start: Vec<usize>, entries: Vec<(usize, f64, f64)>,
+    /// Reused buffers for the scatter pass.
+    next: Vec<usize>, sorted: Vec<(usize, f64, f64)>,
+}
+
+/// Upper bound on cells per axis, so a single stray point far away cannot blow up the grid.
+const MAX_CELLS_PER_AXIS: usize = 256;
+
+impl SpatialGrid {
+    /// `cell_size` should be about the radius of the typical query.
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            base_cell_size: cell_size, cell_size, origin: (0.0, 0.0), cols: 0, rows: 0,
+            cell_start: Vec::new(), entries: Vec::new(), next: Vec::new(), sorted: Vec::new(),
+        }
+    }
+
+    /// Replaces the contents with `points`; the position of each point in the iterator is its index in queries.
+    pub fn rebuild(&mut self, points: impl Iterator<Item = (f64, f64)>) {
+        self.entries.clear();
+        self.entries.extend(points.enumerate().map(|(i, (x, y))| (i, x, y)));
+        if self.entries.is_empty() { self.cols = 0; self.rows = 0; self.cell_start.clear(); return; }
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for &(_, x, y) in &self.entries { min_x = min_x.min(x); min_y = min_y.min(y); max_x = max_x.max(x); max_y = max_y.max(y); }
+        let extent = (max_x - min_x).max(max_y - min_y);
+        self.cell_size = self.base_cell_size.max(extent / (MAX_CELLS_PER_AXIS - 1) as f64);
+        self.origin = (min_x, min_y);
+        self.cols = ((max_x - min_x) / self.cell_size) as usize + 1;
+        self.rows = ((max_y - min_y) / self.cell_size) as usize + 1;
+
+        // Counting sort: count per cell, turn the counts into start offsets, then scatter.
+        self.cell_start.clear(); self.cell_start.resize(self.cols * self.rows + 1, 0);
+        for &(_, x, y) in &self.entries { let c = self.cell_of(x, y); self.cell_start[c + 1] += 1; }
+        for c in 0..self.cols * self.rows { self.cell_start[c + 1] += self.cell_start[c]; }
+        self.next.clear(); self.next.extend_from_slice(&self.cell_start);
+        self.sorted.clear(); self.sorted.resize(self.entries.len(), (0, 0.0, 0.0));
+        for &entry in &self.entries { let c = self.cell_of(entry.1, entry.2); self.sorted[self.next[c]] = entry; self.next[c] += 1; }
+        std::mem::swap(&mut self.entries, &mut self.sorted);
+    }
+
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    fn cell_of(&self, x: f64, y: f64) -> usize {
+        let (col, row) = self.cell_coords(x, y);
+        row.clamp(0, self.rows as i64 - 1) as usize * self.cols + col.clamp(0, self.cols as i64 - 1) as usize
+    }
+    /// Cell coordinates of `(x, y)`; may lie outside the grid for points that are.
+    fn cell_coords(&self, x: f64, y: f64) -> (i64, i64) {
+        (((x - self.origin.0) / self.cell_size).floor() as i64, ((y - self.origin.1) / self.cell_size).floor() as i64)
+    }
+    fn cell(&self, col: i64, row: i64) -> &[(usize, f64, f64)] {
+        if col < 0 || row < 0 || col >= self.cols as i64 || row >= self.rows as i64 { return &[]; }
+        let c = row as usize * self.cols + col as usize;
+        &self.entries[self.cell_start[c]..self.cell_start[c + 1]]
+    }
+
+    /// Calls `visit(index, x, y)` for every point within `radius` of `(x, y)`.
+    pub fn for_each_within(&self, x: f64, y: f64, radius: f64, mut visit: impl FnMut(usize, f64, f64)) {
+        if self.is_empty() { return; }
+        let (min_col, min_row) = self.cell_coords(x - radius, y - radius);
+        let (max_col, max_row) = self.cell_coords(x + radius, y + radius);
+        for row in min_row.max(0)..=max_row.min(self.rows as i64 - 1) {
+            for col in min_col.max(0)..=max_col.min(self.cols as i64 - 1) {
+                for &(i, px, py) in self.cell(col, row) {
+                    if (px - x).powi(2) + (py - y).powi(2) < radius * radius { visit(i, px, py); }
+                }
+            }
+        }
+    }
+
+    /// The point closest to `(x, y)` as `(distance squared, index, x, y)`, searching outwards ring by ring.
+    pub fn nearest(&self, x: f64, y: f64) -> Option<(f64, usize, f64, f64)> {
+        if self.is_empty() { return None; }
+        let (col, row) = self.cell_coords(x, y);
+        let (last_col, last_row) = (self.cols as i64 - 1, self.rows as i64 - 1);
+        // Ring `r` is the square of cells `r` steps from the query's cell; skip those wholly outside the grid.
+        let first_ring = 0.max(-col).max(col - last_col).max(-row).max(row - last_row);
+        let last_ring = col.abs().max((col - last_col).abs()).max(row.abs()).max((row - last_row).abs());
+        let mut best: Option<(f64, usize, f64, f64)> = None;
+        for ring in first_ring..=last_ring {
+            // Every cell in ring `r` is at least `r - 1` cells away, so nothing there can beat a closer hit.
+            if let Some((dist_sq, ..)) = best && ((ring - 1) as f64 * self.cell_size).powi(2) >= dist_sq { break; }
+            let mut check = |c: i64, r: i64| {
+                for &(i, px, py) in self.cell(c, r) {
+                    let dist_sq = (px - x).powi(2) + (py - y).powi(2);
+                    if best.is_none_or(|b| dist_sq < b.0) { best = Some((dist_sq, i, px, py)); }
+                }
+            };
+            if ring == 0 { check(col, row); continue; }
+            for c in col - ring..=col + ring { check(c, row - ring); check(c, row + ring); }
+            for r in row - ring + 1..row + ring { check(col - ring, r); check(col + ring, r); }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn queries_match_a_brute_force_scan() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let points: Vec<(f64, f64)> = (0..500).map(|_| (rng.gen_range(-200.0..1400.0), rng.gen_range(-50.0..900.0))).collect();
+        let mut grid = SpatialGrid::new(40.0);
+        grid.rebuild(points.iter().copied());
+        for _ in 0..200 {
+            let (x, y) = (rng.gen_range(-400.0..1600.0), rng.gen_range(-200.0..1100.0));
+            let dist_sq = |&(px, py): &(f64, f64)| (px - x).powi(2) + (py - y).powi(2);
+            let expected = points.iter().map(dist_sq).fold(f64::MAX, f64::min);
+            assert_eq!(grid.nearest(x, y).map(|n| n.0), Some(expected));
+            let mut within = Vec::new();
+            grid.for_each_within(x, y, 90.0, |i, _, _| within.push(i));
+            within.sort_unstable();
+            let expected: Vec<usize> = (0..points.len()).filter(|&i| dist_sq(&points[i]) < 90.0 * 90.0).collect();
+            assert_eq!(within, expected);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+// crates/aquarium/src/species.rs
+use std::f64::consts::PI;
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+use crate::renderer::Renderer;
+
+/// The kinds of fish the tank can hold. Each has its own silhouette, colors, size, pace, depth and schooling habit.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Species { Tetra, Angelfish, Clownfish, Pufferfish, Eel, Barracuda }
+
+/// How strongly a fish keeps its distance from, matches the heading of and moves towards its schoolmates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlockWeights { pub separation: f64, pub alignment: f64, pub cohesion: f64 }
+
+/// Everything that tells one species apart. Ranges are sampled per fish; `size` is half the body length in pixels,
+/// speeds are in pixels per second and forces in pixels per second squared.
+pub struct SpeciesSpec {
+    pub size: (f64, f64),
+    pub max_speed: (f64, f64),
+    pub max_force: (f64, f64),
+    /// Preferred swimming depth as fractions of the tank height, top to bottom.
+    pub depth: (f64, f64),
+    pub flock: FlockWeights,
+    /// `(body, accent)` color pairs to pick from.
+    pub colors: &'static [(&'static str, &'static str)],
+    /// Hunts smaller fish of other species instead of eating food pellets.
+    pub predator: bool,
+}
+
+const TETRA: SpeciesSpec = SpeciesSpec {
+    size: (7.0, 11.0), max_speed: (24.0, 40.0), max_force: (60.0, 120.0), depth: (0.15, 0.6),
+    flock: FlockWeights { separation: 1.5, alignment: 1.2, cohesion: 1.0 },
+    colors: &[("hsl(200, 85%, 62%)", "hsl(355, 85%, 55%)"), ("hsl(185, 80%, 58%)", "hsl(350, 80%, 52%)"), ("hsl(210, 70%, 70%)", "hsl(5, 90%, 58%)")],
+    predator: false,
+};
+const ANGELFISH: SpeciesSpec = SpeciesSpec {
+    size: (13.0, 19.0), max_speed: (14.0, 24.0), max_force: (30.0, 70.0), depth: (0.2, 0.7),
+    flock: FlockWeights { separation: 1.5, alignment: 0.6, cohesion: 0.4 },
+    colors: &[("hsl(45, 30%, 85%)", "hsl(30, 15%, 20%)"), ("hsl(40, 85%, 62%)", "hsl(30, 40%, 25%)"), ("hsl(220, 15%, 78%)", "hsl(220, 20%, 18%)")],
+    predator: false,
+};
+const CLOWNFISH: SpeciesSpec = SpeciesSpec {
+    size: (9.0, 13.0), max_speed: (18.0, 30.0), max_force: (40.0, 90.0), depth: (0.5, 0.85),
+    flock: FlockWeights { separation: 1.5, alignment: 0.3, cohesion: 0.5 },
+    colors: &[("hsl(22, 95%, 55%)", "white"), ("hsl(15, 90%, 50%)", "white"), ("hsl(35, 95%, 55%)", "white")],
+    predator: false,
+};
+const PUFFERFISH: SpeciesSpec = SpeciesSpec {
+    size: (11.0, 16.0), max_speed: (10.0, 18.0), max_force: (20.0, 50.0), depth: (0.3, 0.8),
+    flock: FlockWeights { separation: 1.5, alignment: 0.0, cohesion: 0.0 },
+    colors: &[("hsl(50, 55%, 65%)", "hsl(30, 35%, 30%)"), ("hsl(80, 30%, 60%)", "hsl(90, 25%, 25%)")],
+    predator: false,
+};
+const EEL: SpeciesSpec = SpeciesSpec {
+    size: (16.0, 24.0), max_speed: (12.0, 22.0), max_force: (30.0, 60.0), depth: (0.65, 0.85),
+    flock: FlockWeights { separation: 1.5, alignment: 0.0, cohesion: 0.0 },
+    colors: &[("hsl(100, 30%, 35%)", "hsl(55, 60%, 55%)"), ("hsl(30, 35%, 30%)", "hsl(40, 50%, 60%)")],
+    predator: false,
+};
+const BARRACUDA: SpeciesSpec = SpeciesSpec {
+    size: (22.0, 30.0), max_speed: (20.0, 34.0), max_force: (40.0, 90.0), depth: (0.2, 0.7),
+    flock: FlockWeights { separation: 1.5, alignment: 0.0, cohesion: 0.0 },
+    colors: &[("hsl(210, 12%, 72%)", "hsl(215, 20%, 30%)"), ("hsl(195, 15%, 65%)", "hsl(200, 25%, 25%)")],
+    predator: true,
+};
+
+/// Where a fish is in its swimming stroke. The body bends in a wave that travels from behind the head to the tail
+/// tip, while the pectoral fins paddle on their own beat.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pose {
+    /// Phase of the body wave in radians.
+    pub phase: f64,
+    /// Sideways swing of the tail tip as a fraction of the fish's size.
+    pub amplitude: f64,
+    /// Pectoral fin stroke, from -1 (folded back) to 1 (spread).
+    pub fin: f64,
+}
+impl Pose {
+    /// Straight and still.
+    pub const REST: Pose = Pose { phase: 0.0, amplitude: 0.0, fin: 0.0 };
+}
+
+/// Wave number of the body wave: radians of phase per fish size along the spine.
+const BODY_WAVE: f64 = 2.5;
+
+impl Species {
+    /// Every species except the predators, which a tank only gets when its config lists them.
+    pub const PEACEFUL: [Species; 5] = [Species::Tetra, Species::Angelfish, Species::Clownfish, Species::Pufferfish, Species::Eel];
+
+    /// The species with the given config name, such as `"clownfish"`.
+    pub fn from_name(name: &str) -> Option<Species> {
+        Species::deserialize(IntoDeserializer::<serde::de::value::Error>::into_deserializer(name)).ok()
+    }
+
+    pub fn spec(self) -> &'static SpeciesSpec {
+        match self {
+            Species::Tetra => &TETRA, Species::Angelfish => &ANGELFISH, Species::Clownfish => &CLOWNFISH,
+            Species::Pufferfish => &PUFFERFISH, Species::Eel => &EEL, Species::Barracuda => &BARRACUDA,
+        }
+    }
+
+    /// The body ellipse in the fish's own frame as `(center_x, radius_x, radius_y)`, in multiples of `size`.
+    pub fn body(self) -> (f64, f64, f64) {
+        match self {
+            Species::Tetra => (0.0, 1.0, 0.45), Species::Angelfish => (0.0, 0.8, 0.75), Species::Clownfish => (0.0, 1.0, 0.55),
+            Species::Pufferfish => (0.0, 0.85, 0.85), Species::Eel => (-0.5, 1.5, 0.35), Species::Barracuda => (0.0, 1.0, 0.26),
+        }
+    }
+
+    /// Paints the fish in its own frame: nose towards +x, centered on the origin, `size` as the half length, with the
+    /// body bent to `pose`.
+    pub fn draw(self, ctx: &mut dyn Renderer, size: f64, body: &str, accent: &str, pose: Pose) {
+        let tail_end = match self { Species::Eel => 2.0, _ => 1.5 } * size;
+        let ctx = &mut Bent { inner: ctx, size, tail_end, pose, open_path: false };
+        ctx.set_fill_color(body);
+        match self {
+            Species::Tetra => {
+                tail(ctx, size, 0.9, 1.5, 0.55);
+                ctx.begin_path(); ctx.ellipse(0.0, 0.0, size, size * 0.45, 0.0, 0.0, PI * 2.0); ctx.fill();
+                // The neon stripe along the rear half of the body.
+                ctx.set_fill_color(accent); ctx.begin_path(); ctx.ellipse(-size * 0.3, size * 0.1, size * 0.6, size * 0.12, 0.0, 0.0, PI * 2.0); ctx.fill();
+            }
+            Species::Angelfish => {
+                // Tall triangular fins above and below a short, deep body.
+                ctx.begin_path(); ctx.move_to(size * 0.3, -size * 0.5); ctx.line_to(-size * 0.9, -size * 1.6); ctx.line_to(-size * 0.5, -size * 0.3); ctx.close_path(); ctx.fill();
+                ctx.begin_path(); ctx.move_to(size * 0.3, size * 0.5); ctx.line_to(-size * 0.9, size * 1.6); ctx.line_to(-size * 0.5, size * 0.3); ctx.close_path(); ctx.fill();
+                tail(ctx, size, 0.7, 1.2, 0.45);
+                ctx.begin_path(); ctx.ellipse(0.0, 0.0, size * 0.8, size * 0.75, 0.0, 0.0, PI * 2.0); ctx.fill();
+                ctx.save();
+                ctx.begin_path(); ctx.ellipse(0.0, 0.0, size * 0.8, size * 0.75, 0.0, 0.0, PI * 2.0); ctx.clip();
+                ctx.set_fill_color(accent);
+                for x in [-0.45, 0.0, 0.4] { ctx.fill_rect(size * x - size * 0.06, -size, size * 0.12, size * 2.0); }
+                ctx.restore();
+            }
+            Species::Clownfish => {
+                tail(ctx, size, 0.85, 1.35, 0.5);
+                ctx.begin_path(); ctx.ellipse(0.0, 0.0, size, size * 0.55, 0.0, 0.0, PI * 2.0); ctx.fill();
+                ctx.save();
+                ctx.begin_path(); ctx.ellipse(0.0, 0.0, size, size * 0.55, 0.0, 0.0, PI * 2.0); ctx.clip();
+                ctx.set_fill_color(accent);
+                for x in [-0.65, -0.05, 0.45] { ctx.fill_rect(size * x - size * 0.09, -size, size * 0.18, size * 2.0); }
+                ctx.restore();
+            }
+            Species::Pufferfish => {
+                tail(ctx, size, 0.8, 1.15, 0.35);
+                ctx.begin_path(); ctx.arc(0.0, 0.0, size * 0.85, 0.0, PI * 2.0); ctx.fill();
+                ctx.set_fill_color(accent);
+                for (x, y) in [(-0.4, -0.35), (-0.1, -0.55), (0.15, -0.3), (-0.45, 0.1), (-0.15, 0.0), (0.2, 0.15)] {
+                    ctx.begin_path(); ctx.arc(size * x, size * y, size * 0.07, 0.0, PI * 2.0); ctx.fill();
+                }
+            }
+            Species::Eel => {
+                // A long body that tapers to the tail in a gentle S.
+                let (length, thickness) = (size * 3.0, size * 0.35);
+                let spine = |t: f64| (size - t * length, (t * PI * 2.0).sin() * size * 0.25 * t);
+                let half_width = |t: f64| thickness * (1.0 - t * 0.8) * (t * 8.0).min(1.0).sqrt();
+                ctx.begin_path();
+                for i in 0..=16 { let t = i as f64 / 16.0; let (x, y) = spine(t); if i == 0 { ctx.move_to(x, y); } else { ctx.line_to(x, y - half_width(t)); } }
+                for i in (0..16).rev() { let t = i as f64 / 16.0; let (x, y) = spine(t); ctx.line_to(x, y + half_width(t)); }
+                ctx.close_path(); ctx.fill();
+                ctx.set_stroke_color(accent); ctx.set_line_width(size * 0.08);
+                ctx.begin_path();
+                for i in 1..=10 { let (x, y) = spine(i as f64 / 12.0); if i == 1 { ctx.move_to(x, y - thickness * 0.2); } else { ctx.line_to(x, y - thickness * 0.2); } }
+                ctx.stroke();
+            }
+            Species::Barracuda => {
+                tail(ctx, size, 0.9, 1.4, 0.45);
+                // Slender body with a pointed, underslung snout.
+                ctx.begin_path(); ctx.ellipse(0.0, 0.0, size, size * 0.26, 0.0, 0.0, PI * 2.0); ctx.fill();
+                ctx.begin_path(); ctx.move_to(size * 0.7, -size * 0.2); ctx.line_to(size * 1.3, size * 0.02); ctx.line_to(size * 0.7, size * 0.2); ctx.close_path(); ctx.fill();
+                ctx.begin_path(); ctx.move_to(-size * 0.2, -size * 0.22); ctx.line_to(-size * 0.45, -size * 0.5); ctx.line_to(-size * 0.5, -size * 0.2); ctx.close_path(); ctx.fill();
+                ctx.save();
+                ctx.begin_path(); ctx.ellipse(0.0, 0.0, size, size * 0.26, 0.0, 0.0, PI * 2.0); ctx.clip();
+                ctx.set_fill_color(accent);
+                for x in [-0.7, -0.4, -0.1, 0.2] { ctx.fill_rect(size * x, -size * 0.3, size * 0.1, size * 0.3); }
+                ctx.restore();
+            }
+        }
+        if self != Species::Eel { ctx.set_fill_color(accent); pectoral_fin(ctx, size, pose.fin); }
+        let eye_x = match self { Species::Eel | Species::Barracuda => size * 0.75, _ => size * 0.6 };
+        ctx.set_fill_color("white"); ctx.begin_path(); ctx.arc(eye_x, -size * 0.05, size * 0.13, 0.0, PI * 2.0); ctx.fill();
+        ctx.set_fill_color("black"); ctx.begin_path(); ctx.arc(eye_x + size * 0.04, -size * 0.05, size * 0.07, 0.0, PI * 2.0); ctx.fill();
+    }
+}
+
+/// A small fin behind the gills that paddles with `flap`.
+fn pectoral_fin(ctx: &mut dyn Renderer, size: f64, flap: f64) {
+    let (root_x, root_y, length, width) = (size * 0.25, size * 0.15, size * 0.4, size * 0.12);
+    let angle = PI * 0.8 + flap * 0.45;
+    let (dx, dy) = (angle.cos(), angle.sin());
+    ctx.begin_path(); ctx.move_to(root_x - dy * width, root_y + dx * width); ctx.line_to(root_x + dx * length, root_y + dy * length);
+    ctx.line_to(root_x + dy * width, root_y - dx * width); ctx.close_path(); ctx.fill();
+}
+
+/// Bends everything drawn through it along the fish's spine: points are shifted sideways by the body wave, more the
+/// further back they are, while the head stays rigid. Curves are flattened to line segments first so they bend too.
+struct Bent<'a> { inner: &'a mut dyn Renderer, size: f64, tail_end: f64, pose: Pose, open_path: bool }
+impl Bent<'_> {
+    fn bend(&self, x: f64, y: f64) -> (f64, f64) {
+        let head = self.size * 0.3;
+        let along = ((head - x) / (head + self.tail_end)).clamp(0.0, 1.2);
+        (x, y + self.pose.amplitude * self.size * along * along * (self.pose.phase - BODY_WAVE * (head - x) / self.size).sin())
+    }
+    /// Flattened elliptical arc, joined to the current point like the Canvas `arc` and `ellipse`.
+    #[allow(clippy::too_many_arguments)]
+    fn curve(&mut self, x: f64, y: f64, radius_x: f64, radius_y: f64, rotation: f64, start_angle: f64, end_angle: f64) {
+        let sweep = if end_angle - start_angle >= PI * 2.0 { PI * 2.0 } else { (end_angle - start_angle).rem_euclid(PI * 2.0) };
+        let steps = ((sweep / (PI * 2.0)) * 24.0).ceil().max(2.0) as usize;
+        let (sin_r, cos_r) = rotation.sin_cos();
+        for i in 0..=steps {
+            let angle = start_angle + sweep * i as f64 / steps as f64;
+            let (ex, ey) = (radius_x * angle.cos(), radius_y * angle.sin());
+            let (px, py) = (x + ex * cos_r - ey * sin_r, y + ex * sin_r + ey * cos_r);
+            if i == 0 && !self.open_path { self.move_to(px, py); } else { self.line_to(px, py); }
+        }
+    }
+}
+impl Renderer for Bent<'_> {
+    fn save(&mut self) { self.inner.save(); }
+    fn restore(&mut self) { self.inner.restore(); }
+    fn translate(&mut self, x: f64, y: f64) { self.inner.translate(x, y); }
+    fn rotate(&mut self, angle: f64) { self.inner.rotate(angle); }
+    fn scale(&mut self, x: f64, y: f64) { self.inner.scale(x, y); }
+    fn begin_path(&mut self) { self.open_path = false; self.inner.begin_path(); }
+    fn move_to(&mut self, x: f64, y: f64) { let (x, y) = self.bend(x, y); self.open_path = true; self.inner.move_to(x, y); }
+    fn line_to(&mut self, x: f64, y: f64) {
+        if !self.open_path { return self.move_to(x, y); }
+        let (x, y) = self.bend(x, y); self.inner.line_to(x, y);
+    }
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) { self.curve(x, y, radius, radius, 0.0, start_angle, end_angle); }
+    fn ellipse(&mut self, x: f64, y: f64, radius_x: f64, radius_y: f64, rotation: f64, start_angle: f64, end_angle: f64) {
+        self.curve(x, y, radius_x, radius_y, rotation, start_angle, end_angle);
+    }
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        // Long edges get intermediate points, or a stripe spanning the body would stay straight.
+        self.move_to(x, y);
+        for i in 1..=4 { self.line_to(x + w * i as f64 / 4.0, y); }
+        for i in 1..=4 { self.line_to(x + w - w * i as f64 / 4.0, y + h); }
+        self.close_path();
+    }
+    fn close_path(&mut self) { self.inner.close_path(); }
+    fn fill(&mut self) { self.inner.fill(); }
+    fn stroke(&mut self) { self.inner.stroke(); }
+    fn clip(&mut self) { self.inner.clip(); }
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64) { self.begin_path(); self.rect(x, y, w, h); self.fill(); }
+    fn stroke_rect(&mut self, x: f64, y: f64, w: f64, h: f64) { self.begin_path(); self.rect(x, y, w, h); self.stroke(); }
+    fn set_fill_color(&mut self, color: &str) { self.inner.set_fill_color(color); }
+    fn set_fill_linear_gradient(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, stops: &[(f64, &str)]) { self.inner.set_fill_linear_gradient(x0, y0, x1, y1, stops); }
+    fn set_stroke_color(&mut self, color: &str) { self.inner.set_stroke_color(color); }
+    fn set_line_width(&mut self, width: f64) { self.inner.set_line_width(width); }
+    fn set_blur(&mut self, radius: f64) { self.inner.set_blur(radius); }
+    fn set_global_alpha(&mut self, alpha: f64) { self.inner.set_global_alpha(alpha); }
+}
+
+/// A forked tail starting at `start` body lengths behind the nose and reaching back to `end`.
+fn tail(ctx: &mut dyn Renderer, size: f64, start: f64, end: f64, spread: f64) {
+    ctx.begin_path(); ctx.move_to(-size * start, 0.0); ctx.line_to(-size * end, -size * spread); ctx.line_to(-size * (end - 0.1), 0.0); ctx.line_to(-size * end, size * spread); ctx.close_path(); ctx.fill();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn species_are_found_by_their_config_names() {
+        assert_eq!(Species::from_name("tetra"), Some(Species::Tetra));
+        assert_eq!(Species::from_name("barracuda"), Some(Species::Barracuda));
+        assert_eq!(Species::from_name("Tetra"), None);
+        assert_eq!(Species::from_name("shark"), None);
+        assert!(Species::PEACEFUL.iter().all(|s| !s.spec().predator) && Species::Barracuda.spec().predator);
+    }
+
+    #[test]
+    fn the_body_wave_bends_the_tail_but_not_the_head() {
+        struct Nothing;
+        impl Renderer for Nothing {
+            fn save(&mut self) {}
+            fn restore(&mut self) {}
+            fn translate(&mut self, _: f64, _: f64) {}
+            fn rotate(&mut self, _: f64) {}
+            fn scale(&mut self, _: f64, _: f64) {}
+            fn begin_path(&mut self) {}
+            fn move_to(&mut self, _: f64, _: f64) {}
+            fn line_to(&mut self, _: f64, _: f64) {}
+            fn arc(&mut self, _: f64, _: f64, _: f64, _: f64, _: f64) {}
+            fn ellipse(&mut self, _: f64, _: f64, _: f64, _: f64, _: f64, _: f64, _: f64) {}
+            fn rect(&mut self, _: f64, _: f64, _: f64, _: f64) {}
+            fn close_path(&mut self) {}
+            fn fill(&mut self) {}
+            fn stroke(&mut self) {}
+            fn clip(&mut self) {}
+            fn fill_rect(&mut self, _: f64, _: f64, _: f64, _: f64) {}
+            fn stroke_rect(&mut self, _: f64, _: f64, _: f64, _: f64) {}
+            fn set_fill_color(&mut self, _: &str) {}
+            fn set_fill_linear_gradient(&mut self, _: f64, _: f64, _: f64, _: f64, _: &[(f64, &str)]) {}
+            fn set_stroke_color(&mut self, _: &str) {}
+            fn set_line_width(&mut self, _: f64) {}
+            fn set_blur(&mut self, _: f64) {}
+            fn set_global_alpha(&mut self, _: f64) {}
+        }
+        let pose = Pose { phase: 1.0, amplitude: 0.3, fin: 0.0 };
+        let bent = Bent { inner: &mut Nothing, size: 10.0, tail_end: 15.0, pose, open_path: false };
+        assert_eq!(bent.bend(8.0, 2.0), (8.0, 2.0));
+        let (x, y) = bent.bend(-15.0, 0.0);
+        assert!(x == -15.0 && y.abs() > 0.5, "tail tip at {}", y);
+        let straight = Bent { pose: Pose::REST, ..bent };
+        assert_eq!(straight.bend(-15.0, 0.0), (-15.0, 0.0));
+    }
+}
@@ -0,0 +1,341 @@
+// crates/aquarium/src/web.rs
+use std::cell::RefCell;
+use std::rc::Rc;
+use rand::RngCore;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use crate::canvas::CanvasRenderer;
+use crate::config::{AquariumConfig, Motion, Palette};
+use crate::input::{KeyCommand, PointerInput, PointerKind};
+use crate::species::Species;
+use crate::Aquarium;
+
+/// State of the `requestAnimationFrame` loop. The callback holds the tank but only a weak reference back to this state.
+///
+/// The tank animates while it is `started` and neither the page is `hidden` nor the canvas `offscreen`, so an explicit
+/// `pause` is not undone by the tab becoming visible again. A `still` tank (`motion: still`) never animates; it is
+/// drawn once, and again whenever something changes it. While the tank has pending `input` (a press that may become a
+/// long press, or a fish being dragged) the loop keeps running regardless, to advance just the input.
+#[derive(Default)]
+struct FrameLoop {
+    started: bool, hidden: bool, offscreen: bool, still: bool, input: bool,
+    request_id: Option<i32>, last_timestamp: Option<f64>, callback: Option<Closure<dyn FnMut(f64)>>,
+}
+impl FrameLoop {
+    fn animating(&self) -> bool { self.started && !self.hidden && !self.offscreen && !self.still }
+    fn should_run(&self) -> bool { self.animating() || (self.input && !self.hidden && !self.offscreen) }
+    /// Requests or cancels the next frame to match `should_run`.
+    fn reschedule(&mut self) {
+        let Some(window) = web_sys::window() else { return; };
+        if self.should_run() {
+            if self.request_id.is_some() { return; }
+            let Some(callback) = &self.callback else { return; };
+            self.request_id = window.request_animation_frame(callback.as_ref().unchecked_ref()).ok();
+        } else {
+            if let Some(id) = self.request_id.take() { let _ = window.cancel_animation_frame(id); }
+            // The first frame after a suspension starts a fresh time delta rather than replaying the gap.
+            self.last_timestamp = None;
+        }
+    }
+}
+
+/// Callbacks registered with `on`, by event type (`*` for every event).
+type Listeners = Rc<RefCell<Vec<(String, js_sys::Function)>>>;
+
+/// The JavaScript-facing aquarium: a tank bound to a canvas, driven by its own animation loop.
+#[wasm_bindgen(js_name = Aquarium)]
+pub struct WebAquarium { tank: Rc<RefCell<Aquarium>>, renderer: Rc<RefCell<CanvasRenderer>>, frames: Rc<RefCell<FrameLoop>>, listeners: Listeners, fills_window: bool }
+#[wasm_bindgen(js_class = Aquarium)]
+impl WebAquarium {
+    /// Takes over the canvas with id `canvas_id`. `config` is an optional `AquariumConfig`-shaped object; invalid
+    /// settings are reported as an error rather than a panic. The tank stays still until `start` is called.
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas_id: &str, config: JsValue) -> Result<WebAquarium, JsValue> { Self::from_canvas(canvas_id, AquariumConfig::from_js(config)?) }
+
+    /// Like the constructor with default settings, but every random choice in the tank is drawn from `seed`, so two
+    /// aquariums with the same seed, canvas size and inputs play out identically.
+    pub fn with_seed(canvas_id: &str, seed: u64) -> Result<WebAquarium, JsValue> {
+        Self::from_canvas(canvas_id, AquariumConfig { seed: Some(seed), ..AquariumConfig::default() })
+    }
+
+    fn from_canvas(canvas_id: &str, config: AquariumConfig) -> Result<WebAquarium, JsValue> {
+        // Set up panic hook for better error messages in the console.
+        console_error_panic_hook::set_once();
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas = document.get_element_by_id(canvas_id).ok_or_else(|| JsValue::from_str("Canvas element not found"))?.dyn_into::<HtmlCanvasElement>()?;
+        // The simulation works in CSS pixels. With `fillWindow` the tank takes the window's size and sets the canvas's
+        // CSS size to match. Otherwise it uses the element's layout size (or the window's, for a canvas not laid out
+        // yet), and size containment keeps the backing store, sized by the pixel ratio, from feeding back into layout.
+        let window = web_sys::window().unwrap();
+        let fills_window = config.fill_window;
+        if !fills_window { canvas.style().set_property("contain", "size")?; }
+        let (client_w, client_h) = (canvas.client_width() as f64, canvas.client_height() as f64);
+        let (width, height) = if !fills_window && client_w > 0.0 && client_h > 0.0 { (client_w, client_h) }
+            else { (window.inner_width().unwrap().as_f64().unwrap(), window.inner_height().unwrap().as_f64().unwrap()) };
+        // A tank without area is a problem with the page's layout, not with the settings.
+        if !(width > 0.0 && height > 0.0) { return Err(JsValue::from_str(&format!("Canvas has no area to draw on: {}x{} CSS pixels", width, height))); }
+        let ctx = canvas.get_context("2d")?.ok_or_else(|| JsValue::from_str("Could not get 2d context"))?.dyn_into::<CanvasRenderingContext2d>()?;
+        let mut renderer = CanvasRenderer::new(ctx);
+        if fills_window { renderer.pin_css_size(); }
+        renderer.set_max_pixel_ratio(config.max_pixel_ratio);
+        renderer.resize(width, height);
+        let seed = config.seed.unwrap_or_else(|| rand::thread_rng().next_u64());
+        let tank = Aquarium::with_config(width, height, seed, config).map_err(|e| JsValue::from_str(&format!("Invalid aquarium config: {}", e)))?;
+        let frames = FrameLoop { still: tank.motion() == Motion::Still, ..FrameLoop::default() };
+        Ok(WebAquarium { tank: Rc::new(RefCell::new(tank)), renderer: Rc::new(RefCell::new(renderer)), frames: Rc::new(RefCell::new(frames)), listeners: Rc::default(), fills_window })
+    }
+
+    /// Starts (or resumes) the animation loop. Each frame advances the tank by the time since the previous frame.
+    pub fn start(&self) {
+        let mut frames = self.frames.borrow_mut();
+        if frames.callback.is_none() {
+            let (tank, renderer, listeners, weak_frames) = (self.tank.clone(), self.renderer.clone(), self.listeners.clone(), Rc::downgrade(&self.frames));
+            frames.callback = Some(Closure::new(move |timestamp: f64| {
+                let Some(frames) = weak_frames.upgrade() else { return; };
+                let (dt_ms, animating) = {
+                    let mut frames = frames.borrow_mut();
+                    frames.request_id = None;
+                    if !frames.should_run() { return; }
+                    let dt_ms = frames.last_timestamp.map_or(0.0, |last| timestamp - last);
+                    frames.last_timestamp = Some(timestamp);
+                    (dt_ms, frames.animating())
+                };
+                if animating { tick(&mut tank.borrow_mut(), &mut renderer.borrow_mut(), dt_ms); } else {
+                    let mut tank = tank.borrow_mut();
+                    tank.advance_input(dt_ms / 1000.0);
+                    tank.draw(&mut *renderer.borrow_mut());
+                }
+                dispatch_events(&tank, &listeners);
+                let mut frames = frames.borrow_mut();
+                frames.input = tank.borrow().has_pending_input();
+                frames.reschedule();
+            }));
+        }
+        frames.started = true;
+        frames.reschedule();
+        drop(frames);
+        self.refresh_if_still();
+    }
+    /// Stops the animation loop after the current frame; the tank keeps its state and the canvas its last frame.
+    pub fn pause(&self) { let mut frames = self.frames.borrow_mut(); frames.started = false; frames.reschedule(); }
+    /// Alias of `start`, for symmetry with `pause`.
+    pub fn resume(&self) { self.start(); }
+    /// Whether the tank is currently animating: started, on a visible page and with the canvas on screen.
+    pub fn is_running(&self) -> bool { self.frames.borrow().animating() }
+    /// Suspends the loop while the page is hidden (`document.visibilityState`), without affecting `pause`/`resume`.
+    pub fn set_page_visible(&self, visible: bool) { let mut frames = self.frames.borrow_mut(); frames.hidden = !visible; frames.reschedule(); }
+    /// Suspends the loop while no part of the canvas is in the viewport, without affecting `pause`/`resume`.
+    pub fn set_on_screen(&self, on_screen: bool) { let mut frames = self.frames.borrow_mut(); frames.offscreen = !on_screen; frames.reschedule(); }
+    /// Reports the visitor's `prefers-reduced-motion` media query; with `motion: auto` the tank turns calm to match.
+    pub fn set_prefers_reduced_motion(&self, reduced: bool) {
+        let still = { let mut tank = self.tank.borrow_mut(); tank.set_prefers_reduced_motion(reduced); tank.motion() == Motion::Still };
+        let mut frames = self.frames.borrow_mut(); frames.still = still; frames.reschedule();
+        drop(frames);
+        self.refresh_if_still();
+    }
+    /// Calls `callback` with each event of `event_type` as it happens: `foodDropped`, `foodEaten`, `fishSpawned`,
+    /// `fishDied`, `crabStateChanged` or `bubblePopped`, or `*` for all of them. The callback receives one object with
+    /// the event's `type` and fields, such as `{ type: 'foodEaten', fish: 7, species: 'tetra', x: 312, y: 140 }`.
+    pub fn on(&self, event_type: &str, callback: js_sys::Function) {
+        self.listeners.borrow_mut().push((event_type.to_string(), callback));
+        self.tank.borrow_mut().listen_for_events(true);
+    }
+    /// Removes a callback registered with `on` for the same `event_type`.
+    pub fn off(&self, event_type: &str, callback: &js_sys::Function) {
+        let mut listeners = self.listeners.borrow_mut();
+        listeners.retain(|(t, f)| !(t == event_type && f == callback));
+        self.tank.borrow_mut().listen_for_events(!listeners.is_empty());
+    }
+    /// Stops the animation loop and frees the tank. The JavaScript object cannot be used afterwards.
+    pub fn destroy(self) {}
+
+    pub fn get_castle_scale(&self) -> f64 { self.tank.borrow().get_castle_scale() }
+    pub fn add_food(&self, x: f64, y: f64) { self.tank.borrow_mut().add_food(x, y); self.refresh_if_still(); }
+    /// Adds a fish of a random configured species at `(x, y)`.
+    pub fn add_fish(&self, x: f64, y: f64) { self.tank.borrow_mut().add_fish(x, y); self.refresh_if_still(); }
+    /// Adds a fish of the named species (`"tetra"`, `"angelfish"`, `"clownfish"`, `"pufferfish"` or `"eel"`) at `(x, y)`.
+    pub fn add_fish_of(&self, species: &str, x: f64, y: f64) -> Result<(), JsValue> {
+        let species = Species::from_name(species).ok_or_else(|| JsValue::from_str(&format!("Unknown fish species: {:?}", species)))?;
+        self.tank.borrow_mut().add_fish_of(species, x, y);
+        self.refresh_if_still();
+        Ok(())
+    }
+    pub fn get_seafloor_y_at(&self, x: f64) -> f64 { self.tank.borrow().get_seafloor_y_at(x) }
+    /// Performs the configured interaction for a mouse `button` (as in `MouseEvent.button`) pressed at `(x, y)`.
+    pub fn handle_click(&self, x: f64, y: f64, button: i16) { self.tank.borrow_mut().handle_click(x, y, button); self.refresh_if_still(); }
+    /// Whether a right click or long press does something in the tank, in which case the browser's context menu
+    /// should be suppressed.
+    pub fn captures_context_menu(&self) -> bool { self.tank.borrow().captures_context_menu() }
+    /// Reports a `pointerdown`; the arguments are the event's `pointerId`, `pointerType`, `offsetX`, `offsetY`,
+    /// `button` and `pressure`. Returns whether a fish was picked up.
+    pub fn pointer_down(&self, id: i32, pointer_type: &str, x: f64, y: f64, button: i16, pressure: f64) -> bool {
+        let grabbed = self.tank.borrow_mut().pointer_down(pointer_input(id, pointer_type, x, y, button, pressure));
+        self.sync_input();
+        grabbed
+    }
+    /// Reports a `pointermove`, with the same arguments as `pointer_down`.
+    pub fn pointer_move(&self, id: i32, pointer_type: &str, x: f64, y: f64, button: i16, pressure: f64) {
+        self.tank.borrow_mut().pointer_move(pointer_input(id, pointer_type, x, y, button, pressure));
+        self.refresh_if_still();
+    }
+    /// Reports a `pointerup`, with the same arguments as `pointer_down`.
+    pub fn pointer_up(&self, id: i32, pointer_type: &str, x: f64, y: f64, button: i16, pressure: f64) {
+        self.tank.borrow_mut().pointer_up(pointer_input(id, pointer_type, x, y, button, pressure));
+        self.sync_input();
+    }
+    /// Reports a `pointercancel`: the press is dropped without triggering anything.
+    pub fn pointer_cancel(&self, id: i32) { self.tank.borrow_mut().pointer_cancel(id); self.sync_input(); }
+    /// Reports that a pointer has left the canvas.
+    pub fn pointer_leave(&self, id: i32) { self.tank.borrow_mut().pointer_leave(id); self.sync_input(); }
+    /// Whether fish can be picked up and dragged, in which case the canvas should set `touch-action: none` so that
+    /// dragging a finger moves the fish rather than the page.
+    pub fn drags_fish(&self) -> bool { self.tank.borrow().drags_fish() }
+    pub fn is_holding_fish(&self) -> bool { self.tank.borrow().is_holding_fish() }
+    /// Knocks on the glass at `(x, y)`, startling the fish nearby.
+    pub fn tap_glass(&self, x: f64, y: f64) { self.tank.borrow_mut().tap_glass(x, y); self.refresh_if_still(); }
+    /// Handles a `keydown` by its `KeyboardEvent.key`: F feeds at a random spot, N adds a fish, Space pauses or
+    /// resumes, the arrow keys move the feeding spot, Enter drops food there and T taps the glass. Returns whether the
+    /// key was used, in which case the page should not also act on it.
+    pub fn key_down(&self, key: &str) -> bool {
+        let Some(command) = self.tank.borrow_mut().key_down(key) else { return false; };
+        if command == KeyCommand::Pause {
+            let paused = self.frames.borrow().started;
+            if paused { self.pause(); } else { self.start(); }
+            self.tank.borrow_mut().announce(if paused { "Paused" } else { "Resumed" }.to_string());
+        }
+        self.refresh_if_still();
+        true
+    }
+    /// Whether keys do anything in the tank, in which case the canvas should be focusable and describe its controls.
+    pub fn accepts_keyboard(&self) -> bool { self.tank.borrow().accepts_keyboard() }
+    /// Shows the feeding spot while the canvas has keyboard focus (`:focus-visible`), and hides it otherwise.
+    pub fn set_keyboard_focus(&self, focused: bool) { self.tank.borrow_mut().set_keyboard_focus(focused); self.refresh_if_still(); }
+    /// The latest event worth announcing to screen readers, such as a fish added, if any since the last call.
+    pub fn take_announcement(&self) -> Option<String> { self.tank.borrow_mut().take_announcement() }
+    /// Keeps the loop running while a press or drag needs it, even if the tank itself is not animating, then repaints.
+    fn sync_input(&self) {
+        let input = self.tank.borrow().has_pending_input();
+        let mut frames = self.frames.borrow_mut(); frames.input = input; frames.reschedule();
+        drop(frames);
+        self.refresh_if_still();
+    }
+    /// Repaints a tank that isn't animating and delivers its events, so interactions show while paused or still.
+    /// Every binding that changes the tank ends with it.
+    fn refresh_if_still(&self) {
+        if self.is_running() { return; }
+        self.tank.borrow().draw(&mut *self.renderer.borrow_mut());
+        dispatch_events(&self.tank, &self.listeners);
+    }
+
+    /// How many fish, pellets, crabs and bubbles are in the tank: `{ fish, food, crabs, bubbles }`.
+    pub fn counts(&self) -> Result<JsValue, JsValue> { to_js(&self.tank.borrow().counts()) }
+    /// Every fish as `{ id, species, x, y, vx, vy, size, state, hunger, age, adult, color }`, in drawing order. `state`
+    /// is `swimming`, `feeding`, `startled`, `leaving`, `held` or `dead`.
+    pub fn fish(&self) -> Result<JsValue, JsValue> { to_js(&self.tank.borrow().fish_snapshots()) }
+    /// The fish with `id` (as in events and `fish()`), or `undefined` once it has gone.
+    pub fn get_fish(&self, id: u32) -> Result<JsValue, JsValue> { to_js(&self.tank.borrow().fish_snapshot(id)) }
+    /// Every crab as `{ id, x, y, vx, size, state, leaving }`.
+    pub fn crabs(&self) -> Result<JsValue, JsValue> { to_js(&self.tank.borrow().crab_snapshots()) }
+    /// Every food pellet as `{ x, y, vy }`.
+    pub fn food(&self) -> Result<JsValue, JsValue> { to_js(&self.tank.borrow().food_snapshots()) }
+    /// Every bubble as `{ x, y, vy, size }`.
+    pub fn bubbles(&self) -> Result<JsValue, JsValue> { to_js(&self.tank.borrow().bubble_snapshots()) }
+    /// The topmost fish, crab, pellet or bubble at `(x, y)`, like the matching snapshot with a `kind` added, or
+    /// `undefined` if there is only water.
+    pub fn entity_at(&self, x: f64, y: f64) -> Result<JsValue, JsValue> { to_js(&self.tank.borrow().entity_at(x, y)) }
+
+    /// Sets the fish population target. Surplus fish swim out of the tank and new ones swim in from the sides.
+    pub fn set_fish_count(&self, count: usize) -> Result<(), JsValue> {
+        self.tank.borrow_mut().set_fish_count(count).map_err(|e| JsValue::from_str(&e))?;
+        self.refresh_if_still();
+        Ok(())
+    }
+    /// Sets the crab population target. Surplus crabs walk off the nearer edge; new ones walk in from the sides.
+    pub fn set_crab_count(&self, count: usize) -> Result<(), JsValue> {
+        self.tank.borrow_mut().set_crab_count(count).map_err(|e| JsValue::from_str(&e))?;
+        self.refresh_if_still();
+        Ok(())
+    }
+    /// Sets the bubble population target. Surplus bubbles finish their ascent and are not replaced.
+    pub fn set_bubble_count(&self, count: usize) -> Result<(), JsValue> {
+        self.tank.borrow_mut().set_bubble_count(count).map_err(|e| JsValue::from_str(&e))?;
+        self.refresh_if_still();
+        Ok(())
+    }
+    /// Shows or hides one layer of the scene: `godRays`, `castle`, `seafloor`, `bubbles`, `crabs`, `fish` or `food`.
+    pub fn set_layer_visible(&self, layer: &str, visible: bool) -> Result<(), JsValue> {
+        self.tank.borrow_mut().set_layer_visible(layer, visible).map_err(|e| JsValue::from_str(&e))?;
+        self.refresh_if_still();
+        Ok(())
+    }
+    /// Replaces the color palette; fields missing from `palette` take their default colors. Existing fish are
+    /// recolored only if the new palette lists fish colors.
+    pub fn set_palette(&self, palette: JsValue) -> Result<(), JsValue> {
+        let invalid = |e: String| JsValue::from_str(&format!("Invalid aquarium palette: {}", e));
+        let palette: Palette = serde_wasm_bindgen::from_value(palette).map_err(|e| invalid(e.to_string()))?;
+        self.tank.borrow_mut().set_palette(palette).map_err(invalid)?;
+        self.refresh_if_still();
+        Ok(())
+    }
+
+    /// Adapts to a new canvas size in CSS pixels: resizes the backing store (picking up any change of
+    /// `devicePixelRatio`) and rescales the tank into the new bounds.
+    pub fn resize(&self, width: f64, height: f64) {
+        if width < 1.0 || height < 1.0 { return; }
+        self.renderer.borrow_mut().resize(width, height);
+        self.tank.borrow_mut().resize(width, height);
+        self.refresh_if_still();
+    }
+    /// Whether the tank fills the window (`fillWindow`), in which case the page should call `resize` when the window
+    /// resizes rather than watching the canvas.
+    pub fn fills_window(&self) -> bool { self.fills_window }
+    /// Caps the device pixel ratio used for the backing store; lower values trade sharpness for fill rate.
+    pub fn set_max_pixel_ratio(&self, max_pixel_ratio: f64) { self.renderer.borrow_mut().set_max_pixel_ratio(max_pixel_ratio); self.refresh_if_still(); }
+
+    /// Advances the simulation by `dt_ms` milliseconds and draws a frame, for pages that drive the aquarium from
+    /// their own loop instead of calling `start`. Refused while the built-in loop is started, which would otherwise
+    /// advance the tank twice per frame.
+    pub fn tick(&self, dt_ms: f64) -> Result<(), JsValue> {
+        if self.frames.borrow().started { return Err(JsValue::from_str("tick cannot be used while the animation loop is started; call pause first")); }
+        tick(&mut self.tank.borrow_mut(), &mut self.renderer.borrow_mut(), dt_ms);
+        dispatch_events(&self.tank, &self.listeners);
+        Ok(())
+    }
+}
+impl Drop for WebAquarium {
+    fn drop(&mut self) {
+        // A frame still queued with the browser would call into the freed callback.
+        let mut frames = self.frames.borrow_mut();
+        frames.started = false; frames.reschedule();
+        frames.callback = None;
+    }
+}
+
+fn tick(tank: &mut Aquarium, renderer: &mut CanvasRenderer, dt_ms: f64) {
+    tank.advance(dt_ms / 1000.0);
+    tank.draw(renderer);
+}
+
+fn to_js(value: &impl serde::Serialize) -> Result<JsValue, JsValue> { serde_wasm_bindgen::to_value(value).map_err(JsValue::from) }
+
+/// Hands the tank's events to the matching listeners. Nothing is borrowed during the calls, so listeners may call
+/// back into the aquarium or register and remove listeners.
+fn dispatch_events(tank: &RefCell<Aquarium>, listeners: &Listeners) {
+    let events = tank.borrow_mut().take_events();
+    if events.is_empty() { return; }
+    let listeners = listeners.borrow().clone();
+    for event in events {
+        let Ok(payload) = serde_wasm_bindgen::to_value(&event) else { continue; };
+        let event_type = js_sys::Reflect::get(&payload, &JsValue::from_str("type")).ok().and_then(|t| t.as_string()).unwrap_or_default();
+        for (_, callback) in listeners.iter().filter(|(t, _)| t == "*" || *t == event_type) {
+            if let Err(e) = callback.call1(&JsValue::NULL, &payload) { web_sys::console::error_2(&JsValue::from_str("Aquarium event listener failed:"), &e); }
+        }
+    }
+}
+
+fn pointer_input(id: i32, pointer_type: &str, x: f64, y: f64, button: i16, pressure: f64) -> PointerInput {
+    // Some browsers report no pressure for mice; treat that as a normal press.
+    let pressure = if pressure > 0.0 { pressure } else { 0.5 };
+    PointerInput { id, kind: PointerKind::parse(pointer_type), x, y, button, pressure }
+}
@@ -23,26 +23,128 @@ async function startAquarium(config) {
         await init('./pkg/aquarium_bg.wasm');
 
         // Create an instance of our Rust simulation, telling it which canvas to use.
-        const aquarium = new Aquarium(canvasId);
+        // The whole config object is handed over; Rust validates it and ignores keys it doesn't know (like `target`).
+        const aquarium = new Aquarium(canvasId, config);
+
+        // Every listener is registered with this signal, so `destroy` can detach them all at once.
+        const listeners = new AbortController();
+        const { signal } = listeners;
 
         // --- Event Listeners ---
-        canvas.addEventListener('click', (event) => {
-            aquarium.add_food(event.offsetX, event.offsetY);
-        });
+        // Mouse, pen and touch all arrive as pointer events, which are handed to Rust as they are: it picks up fish,
+        // recognises clicks, taps and long presses per pointer, and performs what `config.interaction` binds them to.
+        // The fish also watch the pointers; how they react is `config.interaction.temperament`. Taps and long presses
+        // work with the browser's touch handling; only dragging fish needs it turned off, at the cost of page scrolling.
+        if (aquarium.drags_fish()) canvas.style.touchAction = 'none';
+        const pointer = (event) => [event.pointerId, event.pointerType, event.offsetX, event.offsetY, event.button, event.pressure];
+        canvas.addEventListener('pointerdown', (event) => {
+            // Keep receiving this pointer's events even if it strays off the canvas while pressed.
+            canvas.setPointerCapture(event.pointerId);
+            aquarium.pointer_down(...pointer(event));
+        }, { signal });
+        canvas.addEventListener('pointermove', (event) => aquarium.pointer_move(...pointer(event)), { signal });
+        canvas.addEventListener('pointerup', (event) => aquarium.pointer_up(...pointer(event)), { signal });
+        canvas.addEventListener('pointercancel', (event) => aquarium.pointer_cancel(event.pointerId), { signal });
+        canvas.addEventListener('pointerleave', (event) => aquarium.pointer_leave(event.pointerId), { signal });
 
+        // The right click and long press are handled above, so the menu they would open is suppressed.
         canvas.addEventListener('contextmenu', (event) => {
-            event.preventDefault();
-            aquarium.add_fish(event.offsetX, event.offsetY);
+            if (aquarium.captures_context_menu()) event.preventDefault();
+        }, { signal });
+
+        // --- Keyboard & Accessibility ---
+        // With keys bound (`config.interaction.keyboard`, on the Rust side), the canvas takes keyboard focus and
+        // describes its controls to screen readers, and what the keys do is read out through a visually hidden live
+        // region. Without them the canvas is left as the page made it.
+        let liveRegion = null;
+        if (aquarium.accepts_keyboard()) {
+            if (!canvas.hasAttribute('tabindex')) canvas.tabIndex = 0;
+            if (!canvas.hasAttribute('role')) canvas.setAttribute('role', 'application');
+            if (!canvas.hasAttribute('aria-label')) {
+                canvas.setAttribute('aria-label', config.label ?? 'Interactive aquarium. Press F to feed the fish, N to add a fish, '
+                    + 'Space to pause, the arrow keys to move the feeding spot, Enter to drop food there and T to tap the glass.');
+            }
+            liveRegion = document.createElement('div');
+            liveRegion.setAttribute('aria-live', 'polite');
+            liveRegion.style.cssText = 'position:absolute;width:1px;height:1px;overflow:hidden;clip-path:inset(50%);white-space:nowrap;';
+            canvas.after(liveRegion);
+            canvas.addEventListener('keydown', (event) => {
+                if (event.ctrlKey || event.metaKey || event.altKey || !aquarium.key_down(event.key)) return;
+                event.preventDefault();
+                const message = aquarium.take_announcement();
+                if (message) liveRegion.textContent = message;
+            }, { signal });
+            canvas.addEventListener('focus', () => aquarium.set_keyboard_focus(canvas.matches(':focus-visible')), { signal });
+            canvas.addEventListener('blur', () => aquarium.set_keyboard_focus(false), { signal });
+        }
+
+        // --- Resizing ---
+        // Watch the canvas itself rather than the window, so containers that resize on their own are handled too.
+        // With `config.fillWindow` the tank sizes the canvas to the window instead, so follow that.
+        const resizeObserver = new ResizeObserver((entries) => {
+            for (const entry of entries) {
+                aquarium.resize(entry.contentRect.width, entry.contentRect.height);
+            }
         });
+        if (aquarium.fills_window()) {
+            window.addEventListener('resize', () => aquarium.resize(window.innerWidth, window.innerHeight), { signal });
+        } else {
+            resizeObserver.observe(canvas);
+        }
 
-        // --- Animation Loop ---
-        function animate() {
-            aquarium.tick();
-            requestAnimationFrame(animate);
+        // Moving the window to a screen with a different pixel density changes devicePixelRatio without resizing
+        // the canvas, so re-apply the current size whenever the ratio changes.
+        function watchPixelRatio() {
+            const query = window.matchMedia(`(resolution: ${window.devicePixelRatio}dppx)`);
+            query.addEventListener('change', () => {
+                aquarium.resize(canvas.clientWidth, canvas.clientHeight);
+                watchPixelRatio();
+            }, { once: true, signal });
         }
-        
-        // Start the loop
-        requestAnimationFrame(animate);
+        watchPixelRatio();
+
+        // --- Visibility ---
+        // Stop ticking while the tab is hidden or the canvas is scrolled out of view. The loop restarts with a fresh
+        // time delta, so the tank picks up where it left off instead of fast-forwarding through the gap.
+        aquarium.set_page_visible(document.visibilityState !== 'hidden');
+        document.addEventListener('visibilitychange', () => {
+            aquarium.set_page_visible(document.visibilityState !== 'hidden');
+        }, { signal });
+
+        const intersectionObserver = new IntersectionObserver((entries) => {
+            const entry = entries[entries.length - 1];
+            aquarium.set_on_screen(entry.isIntersecting);
+        });
+        intersectionObserver.observe(canvas);
+
+        // --- Reduced Motion ---
+        // With `motion: 'auto'` (the default) the tank turns calm for visitors who prefer reduced motion.
+        const reducedMotion = window.matchMedia('(prefers-reduced-motion: reduce)');
+        aquarium.set_prefers_reduced_motion(reducedMotion.matches);
+        reducedMotion.addEventListener('change', (event) => aquarium.set_prefers_reduced_motion(event.matches), { signal });
+
+        // --- Animation Loop ---
+        // The requestAnimationFrame loop lives on the Rust side, which feeds it the real time between frames.
+        aquarium.start();
+
+        // --- Lifecycle ---
+        // The handle is what pages keep; `aquarium` is exposed for the runtime setters.
+        let destroyed = false;
+        return {
+            aquarium,
+            pause() { if (!destroyed) aquarium.pause(); },
+            resume() { if (!destroyed) aquarium.resume(); },
+            // Stops the loop, detaches every listener and frees the wasm object. Safe to call more than once.
+            destroy() {
+                if (destroyed) return;
+                destroyed = true;
+                listeners.abort();
+                liveRegion?.remove();
+                resizeObserver.disconnect();
+                intersectionObserver.disconnect();
+                aquarium.destroy();
+            },
+        };
 
     } catch (e) {
         console.error("Error loading aquarium background:", e);